@@ -0,0 +1,376 @@
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+use crate::{
+    get_deck, is_marriage_card, mix_seed, wins, Action, Card, CardTracker, EGameState, EPlayer,
+    EValue, Game, GameError, GameEvent, GameResult, PlayerView, Search,
+};
+
+/// A player that chooses actions from what it can see of the game
+pub trait Strategy: Send {
+    /// The name of the strategy shown to the players
+    fn name(&self) -> String;
+
+    /// Chooses the next action. Only called when it is the turn of the player.
+    /// The tracker holds what the player remembers of the game so far.
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        tracker: &CardTracker,
+    ) -> Result<Action, GameError>;
+
+    /// Starts choosing the next action, the search of a slow strategy then runs a bit at a time,
+    /// e.g. on a thread or a step per frame. Fast strategies choose right away.
+    fn start(&mut self, view: &PlayerView, tracker: &CardTracker) -> Result<Decision, GameError> {
+        Ok(Decision::Done(self.choose_action(view, tracker)?))
+    }
+}
+
+/// A decision of a [`Strategy`] that is made a bit at a time, see [`Strategy::start`]
+#[derive(Debug, Clone)]
+pub enum Decision {
+    /// the action is chosen
+    Done(Action),
+    /// the search samples more games
+    Searching(Box<Search>),
+}
+
+impl Decision {
+    /// Goes on with the decision. Returns the action once it is chosen.
+    pub fn step(&mut self) -> Result<Option<Action>, GameError> {
+        match self {
+            Decision::Done(action) => Ok(Some(action.clone())),
+            Decision::Searching(search) => {
+                if search.step()? {
+                    return Ok(None);
+                }
+                let action = search.get_best().ok_or(GameError::NotYourTurn)?;
+                *self = Decision::Done(action.clone());
+                Ok(Some(action))
+            }
+        }
+    }
+
+    /// Makes the rest of the decision
+    pub fn run(mut self) -> Result<Action, GameError> {
+        loop {
+            if let Some(action) = self.step()? {
+                return Ok(action);
+            }
+        }
+    }
+}
+
+/// The players at the table. A seat without a strategy is played by a human.
+/// Every seat remembers the cards it has seen in a [`CardTracker`].
+pub struct Seats {
+    pub pc: Option<Box<dyn Strategy>>,
+    pub npc: Option<Box<dyn Strategy>>,
+    pub pc_tracker: CardTracker,
+    pub npc_tracker: CardTracker,
+}
+
+impl Default for Seats {
+    fn default() -> Self {
+        Self::new(None, None)
+    }
+}
+
+impl Seats {
+    pub fn new(pc: Option<Box<dyn Strategy>>, npc: Option<Box<dyn Strategy>>) -> Self {
+        Self {
+            pc,
+            npc,
+            pc_tracker: CardTracker::new(EPlayer::PC, 0.0, 0),
+            npc_tracker: CardTracker::new(EPlayer::NPC, 0.0, 0),
+        }
+    }
+
+    /// Gets the card tracker of the seat
+    pub fn tracker(&self, player: EPlayer) -> &CardTracker {
+        match player {
+            EPlayer::PC => &self.pc_tracker,
+            EPlayer::NPC => &self.npc_tracker,
+        }
+    }
+
+    /// Gets the card tracker of the seat
+    pub fn tracker_mut(&mut self, player: EPlayer) -> &mut CardTracker {
+        match player {
+            EPlayer::PC => &mut self.pc_tracker,
+            EPlayer::NPC => &mut self.npc_tracker,
+        }
+    }
+
+    /// Lets both seats see an event of the game, each tracker ignores what its player can't see
+    pub fn observe(&mut self, event: &GameEvent) {
+        self.pc_tracker.observe(event);
+        self.npc_tracker.observe(event);
+    }
+
+    /// Gets the strategy of the seat
+    pub fn get(&mut self, player: EPlayer) -> Option<&mut Box<dyn Strategy>> {
+        match player {
+            EPlayer::PC => self.pc.as_mut(),
+            EPlayer::NPC => self.npc.as_mut(),
+        }
+    }
+
+    /// Checks if the seat is played by a human
+    pub fn is_human(&self, player: EPlayer) -> bool {
+        match player {
+            EPlayer::PC => self.pc.is_none(),
+            EPlayer::NPC => self.npc.is_none(),
+        }
+    }
+
+    /// Evaluates a complete trick or lets the strategy of the current player act.
+    /// Returns false if the game waits for a human.
+    pub fn step(&mut self, game: &mut Game) -> Result<bool, GameError> {
+        if game.phase() == EGameState::Evaluate {
+            game.apply(Action::Continue)?;
+            return Ok(true);
+        }
+        let Some(decision) = self.start(game)? else {
+            return Ok(false);
+        };
+
+        game.apply(decision.run()?)?;
+        Ok(true)
+    }
+
+    /// Starts the decision of the current player, see [`Strategy::start`].
+    /// Returns None if the game waits for a human or nobody has to decide.
+    pub fn start(&mut self, game: &Game) -> Result<Option<Decision>, GameError> {
+        if game.phase() == EGameState::Evaluate {
+            return Ok(None);
+        }
+        let Some(player) = game.current_player() else {
+            return Ok(None);
+        };
+        let (strategy, tracker) = match player {
+            EPlayer::PC => (self.pc.as_mut(), &self.pc_tracker),
+            EPlayer::NPC => (self.npc.as_mut(), &self.npc_tracker),
+        };
+        let Some(strategy) = strategy else {
+            return Ok(None);
+        };
+
+        strategy.start(&game.view(player), tracker).map(Some)
+    }
+
+    /// Plays the game to the end, all seats must have a strategy
+    pub fn play(&mut self, game: &mut Game) -> Result<GameResult, GameError> {
+        loop {
+            for event in game.drain_events() {
+                self.observe(&event);
+            }
+            if let Some(result) = game.winner() {
+                return Ok(result);
+            }
+            if !self.step(game)? {
+                return Err(GameError::NotYourTurn);
+            }
+        }
+    }
+}
+
+/// A rule-based strategy: keeps trumps and marriage pairs, takes valuable tricks with the cheapest card
+/// and dumps low cards on tricks that can't be won
+#[derive(Debug, Default, Clone)]
+pub struct HeuristicStrategy;
+
+impl Strategy for HeuristicStrategy {
+    fn name(&self) -> String {
+        "Heuristic".to_owned()
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        tracker: &CardTracker,
+    ) -> Result<Action, GameError> {
+        get_heuristic_action(view, &tracker.get_opponent_candidates(view))
+    }
+}
+
+/// Plays a random legal card, only claims 66 when it is sure
+#[derive(Debug, Default, Clone)]
+pub struct RandomStrategy {
+    pub seed: u64,
+}
+
+impl RandomStrategy {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+}
+
+impl Strategy for RandomStrategy {
+    fn name(&self) -> String {
+        "Random".to_owned()
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        _tracker: &CardTracker,
+    ) -> Result<Action, GameError> {
+        if view.points >= 66 && view.can(&Action::ClaimOut) {
+            return Ok(Action::ClaimOut);
+        }
+
+        let mut rng = StdRng::seed_from_u64(get_decision_seed(self.seed, view));
+        view.legal_moves()
+            .choose(&mut rng)
+            .map(|card| Action::Play(card.clone()))
+            .ok_or(GameError::NotYourTurn)
+    }
+}
+
+/// Derives the seed of a decision from the cards the player has seen,
+/// so a decision doesn't depend on the decisions before it
+pub(crate) fn get_decision_seed(seed: u64, view: &PlayerView) -> u64 {
+    let deck = get_deck();
+    view.hand
+        .iter()
+        .chain(view.get_played().iter())
+        .filter_map(|card| deck.iter().position(|c| c == card))
+        .fold(seed, |s, i| mix_seed(s, i as u64 + 1))
+}
+
+/// Chooses an action by a few simple rules, remembering every card of the view
+pub fn heuristic_action(view: &PlayerView) -> Result<Action, GameError> {
+    get_heuristic_action(view, &view.get_opponent_candidates())
+}
+
+/// Chooses an action by a few simple rules, the candidates are the cards the opponent may hold
+fn get_heuristic_action(view: &PlayerView, candidates: &[Card]) -> Result<Action, GameError> {
+    let legal_moves = view.legal_moves();
+    if legal_moves.is_empty() {
+        return Err(GameError::NotYourTurn);
+    }
+
+    let Some(lead) = &view.trick.0 else {
+        return Ok(get_lead_action(view, candidates, legal_moves));
+    };
+
+    Ok(Action::Play(get_follow_card(view, lead, legal_moves)))
+}
+
+/// Chooses what to do when on lead
+fn get_lead_action(view: &PlayerView, candidates: &[Card], legal_moves: Vec<Card>) -> Action {
+    // claim 66 as soon as possible
+    if view.points >= 66 && view.can(&Action::ClaimOut) {
+        return Action::ClaimOut;
+    }
+
+    // always take the trump card when possible
+    if view.can(&Action::ExchangeTrump) {
+        return Action::ExchangeTrump;
+    }
+
+    // declare the most valuable marriage
+    if let Some(suit) = view
+        .get_marriages()
+        .into_iter()
+        .max_by_key(|s| view.get_marriage_value(*s))
+    {
+        return Action::DeclareMarriage(suit);
+    }
+
+    // close the talon when the cards in hand are enough to reach 66
+    if view.can(&Action::Close) && view.points > 0 {
+        let sure_points: usize = view
+            .hand
+            .iter()
+            .filter(|c| is_master(candidates, c) && view.is_trump(c))
+            .map(|c| c.value as usize)
+            .sum();
+        if view.points + sure_points >= 66 {
+            return Action::Close;
+        }
+    }
+
+    // a declared marriage forces the lead, keep the King
+    if view.marriage_lead.is_some() {
+        if let Some(card) = legal_moves.iter().min_by_key(|c| c.value) {
+            return Action::Play(card.clone());
+        }
+    }
+
+    // once the opponent must follow suit, cash in the highest cards
+    if view.must_follow_suit() {
+        if let Some(card) = legal_moves
+            .iter()
+            .filter(|c| is_master(candidates, c))
+            .max_by_key(|c| (view.is_trump(c), c.value))
+        {
+            return Action::Play(card.clone());
+        }
+    }
+
+    // otherwise lead a cheap card that is worth little to the opponent
+    let card = legal_moves
+        .iter()
+        .min_by_key(|c| get_keep_value(view, c))
+        .unwrap_or(&legal_moves[0]);
+    Action::Play(card.clone())
+}
+
+/// Chooses the card to answer the lead of the opponent
+fn get_follow_card(view: &PlayerView, lead: &Card, legal_moves: Vec<Card>) -> Card {
+    // win the trick with the cheapest card possible
+    let winning = legal_moves
+        .iter()
+        .filter(|c| wins(c, lead, view.trump_suit))
+        .min_by_key(|c| get_keep_value(view, c));
+
+    if let Some(card) = winning {
+        let trick_points = lead.value as usize + card.value as usize;
+        let is_valuable = lead.value >= EValue::X;
+        let reaches_66 = view.points + trick_points >= 66;
+        // taking a trick in the same suit costs no trump
+        let is_cheap = card.suit == lead.suit;
+        if is_valuable || reaches_66 || is_cheap || view.must_follow_suit() {
+            return card.clone();
+        }
+    }
+
+    // the trick is not worth it, dump the least valuable card
+    legal_moves
+        .iter()
+        .min_by_key(|c| get_keep_value(view, c))
+        .unwrap_or(&legal_moves[0])
+        .clone()
+}
+
+/// How valuable the card is to keep in the hand: trumps and marriage pairs are kept
+fn get_keep_value(view: &PlayerView, card: &Card) -> usize {
+    let mut value = card.value as usize;
+    if view.is_trump(card) {
+        value += 20;
+    }
+    if is_in_marriage(view, card) {
+        value += 15;
+    }
+    value
+}
+
+/// Checks if the card is part of a marriage pair in the hand of the player
+pub(crate) fn is_in_marriage(view: &PlayerView, card: &Card) -> bool {
+    if !is_marriage_card(card, card.suit) {
+        return false;
+    }
+    let partner = match card.value {
+        EValue::King => Card::new(card.suit, EValue::Ober),
+        _ => Card::new(card.suit, EValue::King),
+    };
+    view.hand.contains(&partner)
+}
+
+/// Checks if no card the opponent may hold can beat the card in its suit
+fn is_master(candidates: &[Card], card: &Card) -> bool {
+    !candidates
+        .iter()
+        .any(|c| c.suit == card.suit && c.value > card.value)
+}
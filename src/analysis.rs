@@ -0,0 +1,155 @@
+use std::collections::VecDeque;
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    evaluate, Action, CardTracker, EPlayer, Game, GameError, MonteCarloStrategy, PlayerView, Trick,
+};
+
+/// The number of sampled games per decision when the cards are not known
+const ITERATIONS: usize = 300;
+
+/// A decision of the analyzed player
+#[derive(Debug, Clone)]
+pub struct MoveReview {
+    pub played: Action,
+    /// the action the engine prefers
+    pub best: Action,
+    /// the estimated game points the played action loses against the best one
+    pub loss: f64,
+    /// the endgame was solved, the loss is exact
+    pub exact: bool,
+}
+
+/// A trick of the analyzed game
+#[derive(Debug, Clone, Default)]
+pub struct TrickReview {
+    pub trick: Trick,
+    /// the decisions of the analyzed player in this trick, e.g. a marriage and a card
+    pub moves: Vec<MoveReview>,
+}
+
+/// Compares the decisions of a player in a game with the search and the endgame solver.
+/// Each call of [`Analyzer::step`] analyzes one decision, so it can run a bit per frame or on a thread.
+#[derive(Debug, Clone)]
+pub struct Analyzer {
+    pub player: EPlayer,
+    pub tricks: Vec<TrickReview>,
+    /// the decisions still to analyze: the trick, what the player knew and what was played
+    pending: VecDeque<(usize, PlayerView, CardTracker, Action)>,
+    total: usize,
+    seed: u64,
+}
+
+impl Analyzer {
+    /// Replays the game and collects the decisions of the player. Forced actions are skipped.
+    pub fn new(game: &Game, player: EPlayer) -> Result<Self, GameError> {
+        let dealer = game.dealer().ok_or(GameError::NotStarted)?;
+        let mut replay = Game::replay(game.seed(), dealer, game.variant.clone(), &[])?;
+
+        let mut tracker = CardTracker::new(player, 0.0, 0);
+        let mut pending = VecDeque::new();
+        for action in game.actions() {
+            replay.drain_events().for_each(|e| tracker.observe(&e));
+            if replay.is_turn_of(player) && replay.legal_actions().len() > 1 {
+                // the decision belongs to the first trick that is not won yet
+                let trick = replay
+                    .tricks()
+                    .iter()
+                    .filter(|t| t.winner.is_some())
+                    .count();
+                pending.push_back((trick, replay.view(player), tracker.clone(), action.clone()));
+            }
+            replay.apply(action.clone())?;
+        }
+
+        let mut tricks: Vec<TrickReview> = replay
+            .tricks()
+            .into_iter()
+            .map(|trick| TrickReview {
+                trick,
+                moves: vec![],
+            })
+            .collect();
+        // a decision after the last trick, e.g. claiming 66
+        if pending.iter().any(|(trick, ..)| *trick == tricks.len()) {
+            tricks.push(TrickReview::default());
+        }
+
+        Ok(Self {
+            player,
+            tricks,
+            total: pending.len(),
+            pending,
+            seed: game.seed(),
+        })
+    }
+
+    /// Analyzes the next decision. Returns false once all decisions are analyzed.
+    pub fn step(&mut self) -> Result<bool, GameError> {
+        let Some((trick, view, tracker, played)) = self.pending.pop_front() else {
+            return Ok(false);
+        };
+
+        // the endgame is solved exactly, otherwise the unseen cards are sampled
+        let exact = tracker.knows_all_cards(&view);
+        let values: Vec<(Action, f64)> = if exact {
+            let game = tracker.determinize(&view, &mut StdRng::seed_from_u64(self.seed));
+            evaluate(&game)?
+                .into_iter()
+                .map(|(a, v)| (a, v as f64))
+                .collect()
+        } else {
+            MonteCarloStrategy::new(ITERATIONS, self.seed).evaluate_actions(&view, &tracker)?
+        };
+
+        let Some((best, best_value)) = values.iter().max_by(|a, b| a.1.total_cmp(&b.1)).cloned()
+        else {
+            return Err(GameError::IllegalMove);
+        };
+        let played_value = values
+            .iter()
+            .find(|(a, _)| *a == played)
+            .map_or(best_value, |(_, v)| *v);
+        let loss = if best == played {
+            0.0
+        } else {
+            (best_value - played_value).max(0.0)
+        };
+
+        if let Some(trick) = self.tricks.get_mut(trick) {
+            trick.moves.push(MoveReview {
+                played,
+                best,
+                loss,
+                exact,
+            });
+        }
+        Ok(!self.pending.is_empty())
+    }
+
+    /// Analyzes all remaining decisions
+    pub fn run(mut self) -> Result<Self, GameError> {
+        while self.step()? {}
+        Ok(self)
+    }
+
+    /// Checks if all decisions are analyzed
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Gets the number of analyzed decisions and of all decisions
+    pub fn progress(&self) -> (usize, usize) {
+        (self.total - self.pending.len(), self.total)
+    }
+
+    /// Gets the estimated game points the player lost in the whole game
+    pub fn get_total_loss(&self) -> f64 {
+        self.tricks
+            .iter()
+            .flat_map(|t| t.moves.iter())
+            .map(|m| m.loss)
+            .sum()
+    }
+}
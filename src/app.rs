@@ -3,63 +3,154 @@ use std::collections::HashMap;
 use egui_extras::RetainedImage;
 use egui_notify::Toasts;
 
-use crate::{get_deck, EPlayer, Game};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    get_deck, Action, Analyzer, Card, CardTracker, Decision, EDifficulty, EGameState, EPlayer,
+    Game, GameError, GameEvent, Hint, HintSearch, Match, PlayerView, Replay, Seats, Trick,
+};
 
 static TEXTURE_SIZE: f32 = 256.0;
 
+/// The estimated game points a decision must lose to be shown as a mistake
+const MISTAKE_LOSS: f64 = 0.25;
+
+/// Takes back the last move of the PC
+const UNDO_SHORTCUT: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::Z);
+
+/// The key of the settings in the storage of eframe
+const SETTINGS_KEY: &str = "settings";
+
+/// The key of the match in progress in the storage of eframe
+const MATCH_KEY: &str = "match";
+
+/// The key of the version of the saved match
+const SAVE_VERSION_KEY: &str = "save_version";
+
+/// The version of the saved match, increase it when [`Match`] or [`Game`] change incompatibly
+const SAVE_VERSION: u32 = 5;
+
+/// The settings that are kept between sessions
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    pub difficulty: EDifficulty,
+    /// the side panel with the tricks of the game is open
+    pub show_tricks: bool,
+    /// casual play, the PC may take back moves. New matches start in this mode.
+    pub casual: bool,
+}
+
 pub struct TemplateApp {
-    game: Game,
+    game_match: Match,
+    seats: Seats,
+    settings: Settings,
     // this how you opt-out of serialization of a member
     //#[serde(skip)]
     toasts: Toasts,
 
     //#[serde(skip)]
     textures: HashMap<String, RetainedImage>,
+
+    /// the text of the "New Match from Seed" dialog, if open
+    seed_dialog: Option<String>,
+
+    /// the Export or Import window, if open
+    notation_dialog: Option<NotationDialog>,
+
+    /// the suggested action for the PC, if asked for
+    hint: Option<Hinting>,
+
+    /// the analysis of the last game, if open
+    review: Option<Review>,
+
+    /// the recorded game that is stepped through, if open
+    replay: Option<ReplayWindow>,
+
+    /// the decision of the computer player, if it is its turn
+    thinking: Option<Thinking>,
+
+    /// the time of the last action, the NPC waits a bit before it acts
+    last_turn_time: f64,
 }
 
 impl Default for TemplateApp {
     fn default() -> Self {
+        let game_match = Match::default();
+        let settings = Settings::default();
         Self {
-            game: Game::new(),
-            textures: HashMap::default(),
+            seats: get_seats(&settings, game_match.seed),
+            game_match,
+            settings,
             toasts: Toasts::default(),
+            textures: HashMap::default(),
+            seed_dialog: None,
+            notation_dialog: None,
+            hint: None,
+            review: None,
+            replay: None,
+            thinking: None,
+            last_turn_time: 0.0,
         }
     }
 }
 
 impl TemplateApp {
     /// Called once before the first frame.
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
         // This is also where you can customize the look and feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
         // Say hello from the terminal to make sure logging is working (also for wasm targets).
         log::info!("App created!");
 
-        // Load previous app state (if any).
-        // Note that you must enable the `persistence` feature for this to work.
-        // if let Some(storage) = cc.storage {
-        //     return eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
-        // }
-
-        Default::default()
+        // Load the settings and the match of the last session (if any).
+        let mut app = Self::default();
+        if let Some(storage) = cc.storage {
+            if let Some(settings) = eframe::get_value::<Settings>(storage, SETTINGS_KEY) {
+                app.settings = settings;
+            }
+            app.game_match.casual = app.settings.casual;
+            if let Some(game_match) = load_match(storage) {
+                app.game_match = game_match;
+            }
+        }
+        app.seats = get_seats(&app.settings, app.game_match.seed);
+        if let Err(e) = restore_game(&mut app.game_match, &mut app.seats) {
+            log::warn!("The saved game is broken, a new match starts: {}", e);
+            app.game_match = Match::new();
+            app.game_match.casual = app.settings.casual;
+            app.seats = get_seats(&app.settings, app.game_match.seed);
+        }
+        app
     }
 }
 
 impl eframe::App for TemplateApp {
     /// Called by the frame work to save state before shutdown.
-    // fn save(&mut self, storage: &mut dyn eframe::Storage) {
-    //     eframe::set_value(storage, eframe::APP_KEY, self);
-    // }
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        eframe::set_value(storage, SETTINGS_KEY, &self.settings);
+        eframe::set_value(storage, SAVE_VERSION_KEY, &SAVE_VERSION);
+        eframe::set_value(storage, MATCH_KEY, &self.game_match);
+    }
 
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         let Self {
-            game,
+            game_match,
+            seats,
+            settings,
             textures,
             toasts,
-            // last_turn_time,
+            seed_dialog,
+            notation_dialog,
+            hint,
+            review,
+            replay,
+            thinking,
+            last_turn_time,
         } = self;
 
         // load all textures once
@@ -67,39 +158,136 @@ impl eframe::App for TemplateApp {
             *textures = load_textures(ctx);
         }
 
+        // book finished games
+        if let Some(bummerl) = game_match.update() {
+            toasts.info(bummerl.to_string());
+        }
+
         // a turn in the game
+        let game = &mut game_match.game;
         let current_time = ctx.input(|i| i.time);
-        let mut allowed = false;
-        if let Some(state) = &game.state {
-            match state {
-                crate::EGameState::None => {}
-                crate::EGameState::PlayerTurn => {
-                    allowed = true;
-                }
-                crate::EGameState::NpcTurn => {
-                    let diff: f64 = current_time - game.last_turn_time;
-                    if diff > 2.0 {
-                        game.do_turn(toasts, current_time);
-                        game.last_turn_time = ctx.input(|i| i.time);
+        let allowed = game.is_turn_of(EPlayer::PC) && seats.is_human(EPlayer::PC);
+        let delay = match game.phase() {
+            EGameState::Evaluate => Some(1.0),
+            EGameState::PlayerTurn if !seats.is_human(EPlayer::PC) => Some(2.0),
+            EGameState::NpcTurn if !seats.is_human(EPlayer::NPC) => Some(2.0),
+            _ => None,
+        };
+        if let Some(delay) = delay {
+            let waited = current_time - *last_turn_time > delay;
+            if game.phase() == EGameState::Evaluate {
+                if waited {
+                    if let Err(e) = game.apply(Action::Continue) {
+                        log::warn!("{}", e);
                     }
+                    *last_turn_time = current_time;
                 }
-                crate::EGameState::Evaluate => {
-                    let diff: f64 = current_time - game.last_turn_time;
-                    if diff > 1.0 {
-                        game.do_turn(toasts, current_time);
-                        game.last_turn_time = ctx.input(|i| i.time);
+            } else {
+                // the computer decides in the background while it waits,
+                // a decision for another position is dropped, e.g. after an undo
+                if !thinking.as_ref().is_some_and(|t| t.is_for(game)) {
+                    *thinking = Some(Thinking::start(seats, game));
+                }
+                let result = thinking.as_mut().and_then(|t| {
+                    t.update();
+                    waited.then(|| t.result.take()).flatten()
+                });
+                if let Some(result) = result {
+                    *thinking = None;
+                    if let Err(e) = result.and_then(|a| game.apply(a).map_err(|e| e.to_string())) {
+                        log::warn!("{}", e);
                     }
+                    *last_turn_time = current_time;
                 }
             }
+            ctx.request_repaint();
         }
+        if !allowed {
+            *hint = None;
+        }
+
+        // moves can only be taken back in casual play, the shortcut is left to text fields with focus
+        let can_undo =
+            game_match.casual && seats.is_human(EPlayer::PC) && !game.actions().is_empty();
+        let mut undo = can_undo
+            && ctx.memory(|m| m.focus().is_none())
+            && ctx.input_mut(|i| i.consume_shortcut(&UNDO_SHORTCUT));
+
+        // a game to open in the replay window
+        let mut open_replay = None;
 
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             // The top panel is often a good place for a menu bar:
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
-                    if ui.button("New Game").clicked() {
-                        *game = Game::new();
-                        game.play(toasts, current_time);
+                    let button = egui::Button::new("Undo")
+                        .shortcut_text(ctx.format_shortcut(&UNDO_SHORTCUT));
+                    if ui.add_enabled(can_undo, button).clicked() {
+                        undo = true;
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    // a game in progress can't be skipped
+                    let can_deal =
+                        game_match.dealer.is_none() || game_match.game.winner().is_some();
+                    if ui
+                        .add_enabled(can_deal, egui::Button::new("Next Game"))
+                        .clicked()
+                    {
+                        if let Err(e) = game_match.next_game() {
+                            toasts.error(e.to_string());
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui.button("New Match").clicked() {
+                        *game_match = Match::new();
+                        game_match.casual = settings.casual;
+                        *seats = get_seats(settings, game_match.seed);
+                        if let Err(e) = game_match.next_game() {
+                            toasts.error(e.to_string());
+                        }
+                        ui.close_menu();
+                    }
+
+                    if ui.button("New Match from Seed...").clicked() {
+                        // the seed of a running game reveals the talon
+                        let seed = match game_match.game.winner() {
+                            Some(_) => game_match.game.seed().to_string(),
+                            None => String::new(),
+                        };
+                        *seed_dialog = Some(seed);
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+
+                    // the seed and the hands are revealed, so only finished games
+                    let finished = game_match.game.winner().is_some();
+                    if ui
+                        .add_enabled(finished, egui::Button::new("Replay Game"))
+                        .clicked()
+                    {
+                        open_replay = Some(game_match.game.clone());
+                        ui.close_menu();
+                    }
+
+                    if ui
+                        .add_enabled(finished, egui::Button::new("Export..."))
+                        .clicked()
+                    {
+                        *notation_dialog =
+                            Some(NotationDialog::Export(game_match.game.to_notation()));
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Import...").clicked() {
+                        *notation_dialog = Some(NotationDialog::Import {
+                            text: String::new(),
+                            error: None,
+                        });
                         ui.close_menu();
                     }
 
@@ -110,135 +298,994 @@ impl eframe::App for TemplateApp {
                         _frame.close();
                     }
                 });
+
+                ui.menu_button("View", |ui| {
+                    if ui.checkbox(&mut settings.show_tricks, "Tricks").clicked() {
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button("Settings", |ui| {
+                    ui.label("Difficulty");
+                    // the opponent of a ranked game can't be changed until the game is over
+                    let is_running =
+                        game_match.dealer.is_some() && game_match.game.winner().is_none();
+                    let can_change = game_match.casual || !is_running;
+                    ui.add_enabled_ui(can_change, |ui| {
+                        for difficulty in EDifficulty::ALL {
+                            if ui
+                                .radio_value(
+                                    &mut settings.difficulty,
+                                    difficulty,
+                                    difficulty.to_string(),
+                                )
+                                .clicked()
+                            {
+                                // the new seats learn the running game again
+                                *seats = get_seats(settings, game_match.seed);
+                                *thinking = None;
+                                if let Err(e) = restore_game(game_match, seats) {
+                                    toasts.error(e.to_string());
+                                }
+                                ui.close_menu();
+                            }
+                        }
+                    })
+                    .response
+                    .on_disabled_hover_text(
+                        "Ranked games are finished at the difficulty they started with",
+                    );
+
+                    ui.separator();
+                    if ui
+                        .checkbox(&mut settings.casual, "Casual (allows Undo)")
+                        .on_hover_text("Applies to the next match")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                    }
+                });
             });
         });
 
-        egui::CentralPanel::default().show(ctx, |ui| {
-            // playing field
-            egui::ScrollArea::vertical().show(ui, |ui| {
-            // trump card
-            if let Some(trump) = &game.trump_card {
-                if let Some(texture) = textures.get(&trump.to_string()) {
-                    let img_size = TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
-                    let r = ui.image(texture.texture_id(ctx), img_size);
-                    r.on_hover_ui(|ui| {
-                        ui.label(trump.to_string());
-                    });
-                } else {
-                    ui.label(trump.to_string());
-                }
-            } else if let Some(trump_suit) = &game.trump_suit {
-                ui.label(trump_suit.to_string());
-            } else {
-                ui.label("[ Trump ]");
+        // take back the last move, from the menu or the shortcut
+        if undo {
+            *hint = None;
+            *review = None;
+            if let Err(e) = undo_move(game_match, seats) {
+                toasts.warning(e.to_string());
             }
+            *last_turn_time = current_time;
+        }
 
-            ui.separator();
-
-            // trick
-            ui.horizontal(|ui| {
-                if let Some(trick0) = &game.trick.0 {
-                    if let Some(texture) = textures.get(&trick0.to_string()) {
-                        let img_size = TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
-                        let r = ui.image(texture.texture_id(ctx), img_size);
-                        r.on_hover_ui(|ui| {
-                            ui.label(trick0.to_string());
-                        });
-                    } else {
-                        ui.label(trick0.to_string());
+        // new match from seed
+        if let Some(text) = seed_dialog {
+            let mut open = true;
+            let mut start = None;
+            egui::Window::new("New Match from Seed")
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    ui.text_edit_singleline(text);
+                    let seed = text.trim().parse::<u64>();
+                    if seed.is_err() {
+                        ui.label("The seed must be a number");
                     }
-                } else {
-                    ui.label("[ trick 0 ]");
+                    if ui
+                        .add_enabled(seed.is_ok(), egui::Button::new("Start"))
+                        .clicked()
+                    {
+                        start = seed.ok();
+                    }
+                });
+
+            if let Some(seed) = start {
+                *game_match = Match::with_seed(seed);
+                game_match.casual = settings.casual;
+                *seats = get_seats(settings, seed);
+                if let Err(e) = game_match.next_game() {
+                    toasts.error(e.to_string());
                 }
+                open = false;
+            }
+            if !open {
+                *seed_dialog = None;
+            }
+        }
 
-                if let Some(trick1) = &game.trick.1 {
-                    if let Some(texture) = textures.get(&trick1.to_string()) {
-                        let img_size = TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
-                        let r = ui.image(texture.texture_id(ctx), img_size);
-                        r.on_hover_ui(|ui| {
-                            ui.label(trick1.to_string());
+        // export or import a game in text notation
+        if let Some(dialog) = notation_dialog {
+            let mut open = true;
+            let mut import = None;
+            match dialog {
+                NotationDialog::Export(text) => {
+                    egui::Window::new("Export").open(&mut open).show(ctx, |ui| {
+                        if ui.button("Copy").clicked() {
+                            ui.output_mut(|o| o.copied_text = text.clone());
+                        }
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            ui.add(egui::TextEdit::multiline(text).code_editor());
                         });
-                    } else {
-                        ui.label(trick1.to_string());
+                    });
+                }
+                NotationDialog::Import { text, error } => {
+                    egui::Window::new("Import").open(&mut open).show(ctx, |ui| {
+                        ui.label("Paste a game in text notation");
+                        ui.horizontal(|ui| {
+                            if ui.button("Import").clicked() {
+                                match Game::from_notation(text) {
+                                    Ok(game) => import = Some(game),
+                                    Err(e) => *error = Some(e.to_string()),
+                                }
+                            }
+                            if ui.button("Replay").clicked() {
+                                match Game::from_notation(text) {
+                                    Ok(game) => open_replay = Some(game),
+                                    Err(e) => *error = Some(e.to_string()),
+                                }
+                            }
+                        });
+                        if let Some(error) = error {
+                            ui.colored_label(egui::Color32::RED, error.as_str());
+                        }
+                        egui::ScrollArea::vertical().show(ui, |ui| {
+                            ui.add(egui::TextEdit::multiline(text).code_editor());
+                        });
+                    });
+                }
+            }
+
+            // the imported game goes on in a new match
+            if let Some(game) = import {
+                let mut imported = Match::with_game(game);
+                imported.casual = settings.casual;
+                let mut imported_seats = get_seats(settings, imported.seed);
+                match restore_game(&mut imported, &mut imported_seats) {
+                    Ok(()) => {
+                        *game_match = imported;
+                        *seats = imported_seats;
+                        *hint = None;
+                        *review = None;
+                        open = false;
+                    }
+                    Err(e) => {
+                        toasts.error(e.to_string());
                     }
-                } else {
-                    ui.label("[ trick 1 ]");
                 }
+            }
+            if open_replay.is_some() {
+                open = false;
+            }
+            if !open {
+                *notation_dialog = None;
+            }
+        }
+
+        // step through a recorded game
+        if let Some(game) = open_replay {
+            match Replay::new(&game) {
+                Ok(r) => {
+                    *replay = Some(ReplayWindow {
+                        replay: r,
+                        reveal: false,
+                    })
+                }
+                Err(e) => {
+                    toasts.error(e.to_string());
+                }
+            }
+        }
+        if let Some(window) = replay {
+            let mut open = true;
+            egui::Window::new("Replay")
+                .open(&mut open)
+                .vscroll(true)
+                .show(ctx, |ui| {
+                    if let Err(e) = show_replay(ui, textures, window) {
+                        toasts.error(e.to_string());
+                    }
+                });
+            if !open {
+                *replay = None;
+            }
+        }
+
+        // score board
+        egui::SidePanel::right("score_board").show(ctx, |ui| {
+            ui.heading("Score");
+            egui::Grid::new("score_grid").striped(true).show(ui, |ui| {
+                ui.label("");
+                ui.label("PC");
+                ui.label("NPC");
+                ui.end_row();
+
+                ui.label("Bummerl");
+                ui.label(game_match.get_bummerl(EPlayer::PC).to_string());
+                ui.label(game_match.get_bummerl(EPlayer::NPC).to_string());
+                ui.end_row();
+
+                ui.label("Remaining");
+                ui.label(game_match.get_points(EPlayer::PC).to_string());
+                ui.label(game_match.get_points(EPlayer::NPC).to_string());
+                ui.end_row();
             });
 
             ui.separator();
 
-            // player hand
-            egui::ScrollArea::horizontal().show(ui, |ui| {
-            ui.horizontal(|ui| {
-                for c in game.player_hand.clone().iter().map(|c| c.to_string()) {
-                    if let Some(texture) = textures.get(&c) {
-                        let img_size = TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
+            ui.label(format!("Difficulty: {}", settings.difficulty));
+            ui.label(if game_match.casual {
+                "Mode: Casual"
+            } else {
+                "Mode: Ranked"
+            });
+            if let Some(dealer) = game_match.dealer {
+                ui.label(format!("Dealer: {}", dealer));
+            }
+            // the seed reveals the talon, it is shown once the game is over
+            if game_match.game.winner().is_some() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Seed: {}", game_match.game.seed()));
+                    if ui.small_button("Copy").clicked() {
+                        ui.output_mut(|o| o.copied_text = game_match.game.seed().to_string());
+                    }
+                });
+            }
+            for (i, result) in game_match.games.iter().enumerate() {
+                ui.label(format!("{}. {}", i + 1, result));
+            }
+            for bummerl in &game_match.bummerl {
+                ui.label(bummerl.to_string());
+            }
+        });
+
+        // the tricks so far
+        egui::SidePanel::left("tricks").show_animated(ctx, settings.show_tricks, |ui| {
+            ui.heading("Tricks");
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                show_history(ui, &game_match.game);
+            });
+        });
+
+        // the action of the PC
+        let mut action = None;
+        let mut start_review = false;
 
-                        let w = egui::ImageButton::new(texture.texture_id(ctx), img_size);
-                        let r = if !allowed {
-                            ui.add_enabled(false, w)
+        // the PC only sees its own cards
+        let view = game_match.game.view(EPlayer::PC);
+        egui::CentralPanel::default().show(ctx, |ui| {
+            // playing field
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                // trump card
+                ui.horizontal(|ui| {
+                    if let Some(closer) = view.closed_by {
+                        // the trump card is turned down
+                        if let Some(trump_suit) = &view.trump_suit {
+                            ui.label(trump_suit.to_string());
+                        }
+                        ui.label(format!("The talon was closed by {}", closer));
+                    } else if let Some(trump) = view.trump_card.clone() {
+                        // the trump card can be clicked to exchange the trump Unter
+                        let can_exchange = allowed && view.can(&Action::ExchangeTrump);
+                        let r = if let Some(texture) = textures.get(&trump.to_string()) {
+                            let img_size =
+                                TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
+                            let r = if can_exchange {
+                                ui.add(egui::ImageButton::new(texture.texture_id(ctx), img_size))
+                            } else {
+                                ui.image(texture.texture_id(ctx), img_size)
+                            };
+                            r.on_hover_ui(|ui| {
+                                ui.label(trump.to_string());
+                            })
+                        } else if can_exchange {
+                            ui.button(trump.to_string())
                         } else {
-                            ui.add(w)
+                            ui.label(trump.to_string())
                         };
-
                         if r.clicked() {
-                            let index = &game
-                                .player_hand
-                                .iter()
-                                .position(|p| p.to_string() == c)
-                                .unwrap();
-                            let card = game.player_hand.swap_remove(*index);
-
-                            game.play_card(card, EPlayer::PC, current_time);
+                            action = Some(Action::ExchangeTrump);
                         }
+                    } else if let Some(trump_suit) = &view.trump_suit {
+                        ui.label(trump_suit.to_string());
+                    } else {
+                        ui.label("[ Trump ]");
+                    }
 
-                        r.on_hover_ui(|ui| {
-                            ui.label(c);
-                        });
+                    // close the talon
+                    if allowed && view.can(&Action::Close) && ui.button("Close").clicked() {
+                        action = Some(Action::Close);
+                    }
+                });
+
+                ui.separator();
+
+                // trick
+                ui.horizontal(|ui| {
+                    if let Some(trick0) = &view.trick.0 {
+                        if let Some(texture) = textures.get(&trick0.to_string()) {
+                            let img_size =
+                                TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
+                            let r = ui.image(texture.texture_id(ctx), img_size);
+                            r.on_hover_ui(|ui| {
+                                ui.label(trick0.to_string());
+                            });
+                        } else {
+                            ui.label(trick0.to_string());
+                        }
                     } else {
-                        let w = egui::Button::new(c.to_string());
-                        let r = if !allowed {
-                            ui.add_enabled(false, w)
+                        ui.label("[ trick 0 ]");
+                    }
+
+                    if let Some(trick1) = &view.trick.1 {
+                        if let Some(texture) = textures.get(&trick1.to_string()) {
+                            let img_size =
+                                TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
+                            let r = ui.image(texture.texture_id(ctx), img_size);
+                            r.on_hover_ui(|ui| {
+                                ui.label(trick1.to_string());
+                            });
                         } else {
-                            ui.add(w)
-                        };
-                        if r.clicked() {
-                            let index = &game
-                                .player_hand
-                                .iter()
-                                .position(|p| p.to_string() == c)
-                                .unwrap();
-                            let card = game.player_hand.swap_remove(*index);
-
-                            game.play_card(card, EPlayer::PC, current_time);
+                            ui.label(trick1.to_string());
+                        }
+                    } else {
+                        ui.label("[ trick 1 ]");
+                    }
+                });
+
+                ui.separator();
+
+                // player hand
+                egui::ScrollArea::horizontal().show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let legal_moves = view.legal_moves();
+                        for card in view.hand.clone() {
+                            let c = card.to_string();
+                            let enabled = allowed && legal_moves.contains(&card);
+                            let hinted = hint
+                                .as_ref()
+                                .and_then(|h| h.get())
+                                .is_some_and(|h| h.action == Action::Play(card.clone()));
+
+                            if let Some(texture) = textures.get(&c) {
+                                let img_size =
+                                    TEXTURE_SIZE * texture.size_vec2() / texture.size_vec2().y;
+
+                                let w = egui::ImageButton::new(texture.texture_id(ctx), img_size)
+                                    .selected(hinted);
+                                let r = ui.add_enabled(enabled, w);
+
+                                if r.clicked() {
+                                    action = Some(Action::Play(card));
+                                }
+
+                                r.on_hover_ui(|ui| {
+                                    ui.label(c);
+                                });
+                            } else {
+                                let w = egui::SelectableLabel::new(hinted, c.to_string());
+                                let r = ui.add_enabled(enabled, w);
+                                if r.clicked() {
+                                    action = Some(Action::Play(card));
+                                }
+                            }
+                        }
+                    });
+                });
+
+                // claim 66 and marriages
+                if allowed {
+                    ui.horizontal(|ui| {
+                        if view.can(&Action::ClaimOut) && ui.button("Claim 66").clicked() {
+                            action = Some(Action::ClaimOut);
+                        }
+
+                        for suit in view.get_marriages() {
+                            let text =
+                                format!("Marriage {} ({})", suit, view.get_marriage_value(suit));
+                            if ui.button(text).clicked() {
+                                action = Some(Action::DeclareMarriage(suit));
+                            }
+                        }
+
+                        // ask the strongest strategy, it only knows what the PC knows
+                        if ui.button("Hint").clicked() {
+                            let seed = game_match.game.seed();
+                            *hint = Some(Hinting::start(&view, seats.tracker(EPlayer::PC), seed));
+                        }
+                    });
+
+                    if let Some(h) = hint {
+                        h.update();
+                        match &h.result {
+                            Some(Ok(h)) => {
+                                ui.label(format!("Hint: {} - {}", h.action, h.reason));
+                            }
+                            Some(Err(e)) => {
+                                toasts.warning(e);
+                                *hint = None;
+                            }
+                            None => {
+                                ui.horizontal(|ui| {
+                                    ui.spinner();
+                                    ui.label("Looking for a hint...");
+                                });
+                                ctx.request_repaint();
+                            }
                         }
                     }
                 }
+
+                // points
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("Points PC: ");
+                    ui.label(view.points.to_string());
+                });
+
+                ui.horizontal(|ui| {
+                    ui.label("Points NPC: ");
+                    ui.label(view.opponent_points.to_string());
+                });
+
+                // winner
+                ui.separator();
+                if let Some(winner) = view.winner {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "The winner is {} with {} game points",
+                            winner.player, winner.game_points
+                        ));
+                        if ui.button("Review").clicked() {
+                            start_review = true;
+                        }
+                    });
+                }
             });
+        });
+
+        // the analysis of the finished game
+        if start_review {
+            *review = Some(Review::start(&game_match.game));
+        }
+        if let Some(r) = review {
+            let mut open = true;
+            egui::Window::new("Review")
+                .open(&mut open)
+                .vscroll(true)
+                .show(ctx, |ui| {
+                    r.update();
+                    match &r.result {
+                        Some(Ok(analyzer)) => show_review(ui, analyzer),
+                        Some(Err(e)) => {
+                            ui.label(e);
+                        }
+                        None => {
+                            ui.horizontal(|ui| {
+                                ui.spinner();
+                                ui.label("Analyzing...");
+                            });
+                            ctx.request_repaint();
+                        }
+                    }
+                });
+            if !open {
+                *review = None;
+            }
+        }
+
+        if let Some(action) = action {
+            *hint = None;
+            if let Err(e) = game_match.game.apply(action) {
+                toasts.warning(e.to_string());
+            }
+            *last_turn_time = current_time;
+        }
+
+        // show what happened in the game
+        for event in game_match.game.drain_events() {
+            seats.observe(&event);
+            if let Some(text) = get_event_text(&event) {
+                toasts.info(text);
+            }
+        }
+        toasts.show(ctx);
+    }
+}
+
+/// The windows to share a game in text notation
+enum NotationDialog {
+    Export(String),
+    Import { text: String, error: Option<String> },
+}
+
+/// A recorded game in the replay window
+struct ReplayWindow {
+    replay: Replay,
+    /// shows the hand of the NPC too
+    reveal: bool,
+}
+
+/// The decision of a computer player. It runs on a thread, on wasm a step per frame.
+struct Thinking {
+    /// the game the decision is for: its seed and the actions so far
+    seed: u64,
+    actions: Vec<Action>,
+    #[cfg(not(target_arch = "wasm32"))]
+    task: Option<std::thread::JoinHandle<Result<Action, GameError>>>,
+    #[cfg(target_arch = "wasm32")]
+    task: Option<Decision>,
+    result: Option<Result<Action, String>>,
+}
+
+impl Thinking {
+    fn start(seats: &mut Seats, game: &Game) -> Self {
+        let mut thinking = Self {
+            seed: game.seed(),
+            actions: game.actions().to_vec(),
+            task: None,
+            result: None,
+        };
+        match seats.start(game) {
+            Ok(Some(Decision::Done(action))) => thinking.result = Some(Ok(action)),
+            #[cfg(not(target_arch = "wasm32"))]
+            Ok(Some(decision)) => thinking.task = Some(std::thread::spawn(move || decision.run())),
+            #[cfg(target_arch = "wasm32")]
+            Ok(Some(decision)) => thinking.task = Some(decision),
+            Ok(None) => thinking.result = Some(Err("Nobody has to decide".to_owned())),
+            Err(e) => thinking.result = Some(Err(e.to_string())),
+        }
+        thinking
+    }
+
+    /// Checks if the decision was started in this position of the game
+    fn is_for(&self, game: &Game) -> bool {
+        self.seed == game.seed() && self.actions == game.actions()
+    }
+
+    /// Takes the result once the thread is finished
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update(&mut self) {
+        if !self.task.as_ref().is_some_and(|t| t.is_finished()) {
+            return;
+        }
+        if let Some(task) = self.task.take() {
+            self.result = Some(match task.join() {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err("The decision failed".to_owned()),
             });
+        }
+    }
 
-            // points
-            ui.separator();
-            ui.horizontal(|ui| {
-                ui.label("Points PC: ");
-                ui.label(game.get_points(EPlayer::PC).to_string());
+    /// Samples a few games per frame
+    #[cfg(target_arch = "wasm32")]
+    fn update(&mut self) {
+        let Some(decision) = &mut self.task else {
+            return;
+        };
+        match decision.step() {
+            Ok(None) => {}
+            Ok(Some(action)) => {
+                self.task = None;
+                self.result = Some(Ok(action));
+            }
+            Err(e) => {
+                self.task = None;
+                self.result = Some(Err(e.to_string()));
+            }
+        }
+    }
+}
+
+/// The hint for the PC. It is worked out on a thread, on wasm a step per frame.
+struct Hinting {
+    #[cfg(not(target_arch = "wasm32"))]
+    task: Option<std::thread::JoinHandle<Result<Hint, GameError>>>,
+    #[cfg(target_arch = "wasm32")]
+    task: Option<HintSearch>,
+    result: Option<Result<Hint, String>>,
+}
+
+impl Hinting {
+    fn start(view: &PlayerView, tracker: &CardTracker, seed: u64) -> Self {
+        let mut hinting = Self {
+            task: None,
+            result: None,
+        };
+        match HintSearch::new(view, tracker, seed) {
+            #[cfg(not(target_arch = "wasm32"))]
+            Ok(search) => hinting.task = Some(std::thread::spawn(move || search.run())),
+            #[cfg(target_arch = "wasm32")]
+            Ok(search) => hinting.task = Some(search),
+            Err(e) => hinting.result = Some(Err(e.to_string())),
+        }
+        hinting
+    }
+
+    /// Gets the hint once it is found
+    fn get(&self) -> Option<&Hint> {
+        self.result.as_ref().and_then(|r| r.as_ref().ok())
+    }
+
+    /// Takes the result once the thread is finished
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update(&mut self) {
+        if !self.task.as_ref().is_some_and(|t| t.is_finished()) {
+            return;
+        }
+        if let Some(task) = self.task.take() {
+            self.result = Some(match task.join() {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err("The hint failed".to_owned()),
             });
+        }
+    }
 
-            ui.horizontal(|ui| {
-                ui.label("Points NPC: ");
-                ui.label(game.get_points(EPlayer::NPC).to_string());
+    /// Samples a few games per frame
+    #[cfg(target_arch = "wasm32")]
+    fn update(&mut self) {
+        let Some(search) = &mut self.task else {
+            return;
+        };
+        match search.step() {
+            Ok(None) => {}
+            Ok(Some(hint)) => {
+                self.task = None;
+                self.result = Some(Ok(hint));
+            }
+            Err(e) => {
+                self.task = None;
+                self.result = Some(Err(e.to_string()));
+            }
+        }
+    }
+}
+
+/// The analysis of a finished game. It runs on a thread, on wasm a step per frame.
+struct Review {
+    #[cfg(not(target_arch = "wasm32"))]
+    task: Option<std::thread::JoinHandle<Result<Analyzer, crate::GameError>>>,
+    #[cfg(target_arch = "wasm32")]
+    task: Option<Analyzer>,
+    result: Option<Result<Analyzer, String>>,
+}
+
+impl Review {
+    fn start(game: &Game) -> Self {
+        let mut review = Self {
+            task: None,
+            result: None,
+        };
+        match Analyzer::new(game, EPlayer::PC) {
+            #[cfg(not(target_arch = "wasm32"))]
+            Ok(analyzer) => review.task = Some(std::thread::spawn(move || analyzer.run())),
+            #[cfg(target_arch = "wasm32")]
+            Ok(analyzer) => review.task = Some(analyzer),
+            Err(e) => review.result = Some(Err(e.to_string())),
+        }
+        review
+    }
+
+    /// Takes the result once the thread is finished
+    #[cfg(not(target_arch = "wasm32"))]
+    fn update(&mut self) {
+        if !self.task.as_ref().is_some_and(|t| t.is_finished()) {
+            return;
+        }
+        if let Some(task) = self.task.take() {
+            self.result = Some(match task.join() {
+                Ok(result) => result.map_err(|e| e.to_string()),
+                Err(_) => Err("The analysis failed".to_owned()),
             });
+        }
+    }
 
-            // winner
-            ui.separator();
-            if let Some(winner) = game.winner {
-                ui.label(format!("The winner is {}", winner));
+    /// Analyzes one decision per frame
+    #[cfg(target_arch = "wasm32")]
+    fn update(&mut self) {
+        let Some(analyzer) = &mut self.task else {
+            return;
+        };
+        match analyzer.step() {
+            Ok(true) => {}
+            Ok(false) => self.result = self.task.take().map(Ok),
+            Err(e) => {
+                self.task = None;
+                self.result = Some(Err(e.to_string()));
             }
-        });
+        }
+    }
+}
 
-            toasts.show(ctx);
-        });
+/// Shows every trick with the decisions of the PC next to the ones of the engine
+fn show_review(ui: &mut egui::Ui, analyzer: &Analyzer) {
+    ui.label(format!(
+        "Estimated loss: {:.2} game points",
+        analyzer.get_total_loss()
+    ));
+    ui.separator();
+
+    egui::Grid::new("review_grid").striped(true).show(ui, |ui| {
+        ui.label("Trick");
+        ui.label("Cards");
+        ui.label("Won by");
+        ui.label("You played");
+        ui.label("Engine");
+        ui.label("Loss");
+        ui.end_row();
+
+        for (i, trick) in analyzer.tricks.iter().enumerate() {
+            let cards: Vec<String> = trick
+                .trick
+                .cards
+                .iter()
+                .map(|(player, card)| format!("{} {}", player, card))
+                .collect();
+            ui.label((i + 1).to_string());
+            ui.label(cards.join(", "));
+            ui.label(
+                trick
+                    .trick
+                    .winner
+                    .map(|p| p.to_string())
+                    .unwrap_or_default(),
+            );
+
+            if trick.moves.is_empty() {
+                ui.end_row();
+            }
+            for (j, review) in trick.moves.iter().enumerate() {
+                // more decisions in a trick get rows of their own
+                if j > 0 {
+                    ui.label("");
+                    ui.label("");
+                    ui.label("");
+                }
+                ui.label(review.played.to_string());
+                ui.label(review.best.to_string());
+                let mut loss = format!("{:.2}", review.loss);
+                if review.exact {
+                    loss.push_str(" (exact)");
+                }
+                if review.loss >= MISTAKE_LOSS {
+                    ui.colored_label(egui::Color32::RED, loss);
+                } else {
+                    ui.label(loss);
+                }
+                ui.end_row();
+            }
+        }
+    });
+}
+
+/// Loads the match of the last session. Saves of other versions or that can't be read are ignored.
+fn load_match(storage: &dyn eframe::Storage) -> Option<Match> {
+    let version = eframe::get_value::<u32>(storage, SAVE_VERSION_KEY)?;
+    if version != SAVE_VERSION {
+        log::warn!(
+            "The saved match has version {}, expected {}",
+            version,
+            SAVE_VERSION
+        );
+        return None;
+    }
+    let game_match = eframe::get_value::<Match>(storage, MATCH_KEY);
+    if game_match.is_none() {
+        log::warn!("The saved match can't be read");
+    }
+    game_match
+}
+
+/// Replays the current game from its seed and actions. This checks the saved game
+/// and lets the trackers of the seats see the game again.
+fn restore_game(game_match: &mut Match, seats: &mut Seats) -> Result<(), GameError> {
+    let saved = &game_match.game;
+    let Some(dealer) = saved.dealer() else {
+        return Ok(());
+    };
+
+    let mut game = Game::replay(saved.seed(), dealer, saved.variant.clone(), saved.actions())?;
+    for event in game.drain_events() {
+        seats.observe(&event);
+    }
+    game_match.game = game;
+    Ok(())
+}
+
+/// Shows the table of a recorded game at the current position with the controls to move through it
+fn show_replay(
+    ui: &mut egui::Ui,
+    textures: &HashMap<String, RetainedImage>,
+    window: &mut ReplayWindow,
+) -> Result<(), GameError> {
+    let ReplayWindow { replay, reveal } = window;
+
+    // controls
+    ui.horizontal(|ui| {
+        let position = replay.position();
+        if ui
+            .add_enabled(position > 0, egui::Button::new("|<"))
+            .clicked()
+        {
+            replay.go_to(0)?;
+        }
+        if ui
+            .add_enabled(position > 0, egui::Button::new("<"))
+            .clicked()
+        {
+            replay.step_back()?;
+        }
+        ui.label(format!("{} / {}", replay.position(), replay.len()));
+        let at_end = position >= replay.len();
+        if ui.add_enabled(!at_end, egui::Button::new(">")).clicked() {
+            replay.step_forward()?;
+        }
+        if ui.add_enabled(!at_end, egui::Button::new(">|")).clicked() {
+            replay.go_to(replay.len())?;
+        }
+        Ok::<(), GameError>(())
+    })
+    .inner?;
+
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Trick");
+        let current = replay.get_trick();
+        for trick in 0..replay.get_trick_starts().len() {
+            if ui
+                .selectable_label(trick == current, (trick + 1).to_string())
+                .clicked()
+            {
+                replay.go_to_trick(trick)?;
+            }
+        }
+        Ok::<(), GameError>(())
+    })
+    .inner?;
+
+    ui.checkbox(reveal, "Reveal both hands");
+    match replay.get_last_action() {
+        Some((Some(player), action)) => ui.label(format!("{}: {}", player, action)),
+        Some((None, _)) => ui.label("The trick was evaluated"),
+        None => ui.label("The cards were dealt"),
+    };
+
+    ui.separator();
+
+    // the table as both players see it
+    let game = replay.game();
+    let pc = game.view(EPlayer::PC);
+    let npc = game.view(EPlayer::NPC);
+    let height = TEXTURE_SIZE / 2.0;
+
+    ui.label("NPC");
+    ui.horizontal_wrapped(|ui| {
+        if *reveal {
+            for card in &npc.hand {
+                show_card(ui, textures, card, height);
+            }
+        } else {
+            ui.label(format!("[ {} cards ]", npc.hand.len()));
+        }
+    });
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        match (&pc.trump_card, pc.closed_by) {
+            (Some(trump), None) => show_card(ui, textures, trump, height),
+            _ => ui.label(pc.trump_suit.map(|s| s.to_string()).unwrap_or_default()),
+        };
+        ui.label(format!("Talon: {} cards", pc.talon_len));
+        if let Some(closer) = pc.closed_by {
+            ui.label(format!("closed by {}", closer));
+        }
+    });
+    ui.horizontal(|ui| {
+        for card in [&pc.trick.0, &pc.trick.1].into_iter().flatten() {
+            show_card(ui, textures, card, height);
+        }
+    });
+    ui.separator();
+
+    ui.label("PC");
+    ui.horizontal_wrapped(|ui| {
+        for card in &pc.hand {
+            show_card(ui, textures, card, height);
+        }
+    });
+
+    ui.separator();
+    ui.label(format!(
+        "Points PC: {}, NPC: {}",
+        pc.points, pc.opponent_points
+    ));
+    if let Some(winner) = pc.winner {
+        ui.label(format!(
+            "The winner is {} with {} game points",
+            winner.player, winner.game_points
+        ));
+    }
+    Ok(())
+}
+
+/// Shows the texture of a card or its name if there is none
+fn show_card(
+    ui: &mut egui::Ui,
+    textures: &HashMap<String, RetainedImage>,
+    card: &Card,
+    height: f32,
+) -> egui::Response {
+    let name = card.to_string();
+    match textures.get(&name) {
+        Some(texture) => {
+            let img_size = height * texture.size_vec2() / texture.size_vec2().y;
+            ui.image(texture.texture_id(ui.ctx()), img_size)
+                .on_hover_text(name)
+        }
+        None => ui.label(name),
+    }
+}
+
+/// Shows the history of the game as the PC saw it: the tricks and what happened between them
+fn show_history(ui: &mut egui::Ui, game: &Game) {
+    let tricks = game.tricks();
+    let mut tricks = tricks.iter().enumerate();
+    for event in game.history() {
+        match event {
+            // a trick is shown once it is taken
+            GameEvent::TrickWon { .. } => {
+                if let Some((i, trick)) = tricks.next() {
+                    show_trick(ui, i, trick);
+                }
+            }
+            GameEvent::GameStarted { .. }
+            | GameEvent::CardDealt { .. }
+            | GameEvent::CardPlayed { .. } => {}
+            _ => {
+                ui.label(event.to_string());
+            }
+        }
+    }
+    // the running trick
+    for (i, trick) in tricks {
+        show_trick(ui, i, trick);
+    }
+}
+
+fn show_trick(ui: &mut egui::Ui, i: usize, trick: &Trick) {
+    let cards: Vec<String> = trick
+        .cards
+        .iter()
+        .map(|(player, card)| format!("{} {}", player, card))
+        .collect();
+    match trick.winner {
+        Some(winner) => ui.label(format!("{}. {} - {}", i + 1, cards.join(", "), winner)),
+        None => ui.label(format!("{}. {}", i + 1, cards.join(", "))),
+    };
+}
+
+/// Takes back the last move of the PC and the reply of the NPC.
+/// The trackers see the replayed game again, so the NPC knows what it knew before.
+fn undo_move(game_match: &mut Match, seats: &mut Seats) -> Result<(), GameError> {
+    game_match.undo(EPlayer::PC)?;
+    for event in game_match.game.drain_events() {
+        seats.observe(&event);
+    }
+    Ok(())
+}
+
+/// The human plays the PC against the strategy of the difficulty
+fn get_seats(settings: &Settings, seed: u64) -> Seats {
+    let difficulty = settings.difficulty;
+    let mut seats = Seats::new(None, Some(difficulty.strategy(seed)));
+    seats.npc_tracker = CardTracker::new(EPlayer::NPC, difficulty.forget_rate(), seed);
+    seats
+}
+
+/// Gets the text of a toast for the event, cards drawn and played are only shown on the table
+fn get_event_text(event: &GameEvent) -> Option<String> {
+    match event {
+        GameEvent::CardDealt { .. } | GameEvent::CardPlayed { .. } => None,
+        GameEvent::TrumpRevealed(_) => None,
+        _ => Some(event.to_string()),
     }
 }
 
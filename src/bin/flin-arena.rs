@@ -0,0 +1,365 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+//! Plays strategies against each other without a window.
+//!
+//! `cargo run --release --bin flin-arena -- --pc hard --npc normal --matches 100 --csv deals.csv`
+
+use std::str::FromStr;
+use std::time::Instant;
+
+use flin::{
+    mix_seed, BummerlResult, CardTracker, EDifficulty, EPlayer, GameError, HeuristicStrategy,
+    Match, RandomStrategy, Seats, Strategy,
+};
+use serde::Serialize;
+
+const USAGE: &str = "Usage: flin-arena [OPTIONS]
+
+Options:
+  --pc <STRATEGY>     the strategy of the PC (default: normal)
+  --npc <STRATEGY>    the strategy of the NPC (default: normal)
+  --matches <N>       the number of matches, a match lasts one Bummerl (default: 100)
+  --seed <SEED>       the seed of the first match, the others derive from it (default: random).
+                      The same seed replays the same run, the search budgets count games, not time.
+  --threads <N>       the number of threads (default: all cores)
+  --csv <PATH>        writes the result of every deal as CSV
+  --json <PATH>       writes the report with the result of every deal as JSON
+
+Strategies: random, heuristic, beginner, normal, hard, expert";
+
+/// A Bummerl is decided after at most 13 games, this only guards against endless matches
+const MAX_GAMES: usize = 100;
+
+struct Options {
+    pc: String,
+    npc: String,
+    matches: usize,
+    seed: u64,
+    threads: usize,
+    csv: Option<String>,
+    json: Option<String>,
+}
+
+/// The result of one deal
+#[derive(Debug, Clone, Serialize)]
+struct DealResult {
+    /// the index of the match
+    game_match: usize,
+    /// the index of the game in the match
+    game: usize,
+    seed: u64,
+    dealer: EPlayer,
+    winner: EPlayer,
+    game_points: usize,
+}
+
+/// The result of one match
+#[derive(Debug, Clone)]
+struct MatchResult {
+    deals: Vec<DealResult>,
+    bummerl: Option<BummerlResult>,
+}
+
+/// A share with its 95% confidence interval
+#[derive(Debug, Clone, Serialize)]
+struct Estimate {
+    value: f64,
+    low: f64,
+    high: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct Report {
+    pc: String,
+    npc: String,
+    seed: u64,
+    matches: usize,
+    games: usize,
+    /// the share of the games the PC won
+    pc_game_rate: Estimate,
+    /// the average game points per game, positive for the PC
+    pc_game_points: Estimate,
+    /// the share of the Bummerl the PC won
+    pc_bummerl_rate: Estimate,
+    pc_bummerl: usize,
+    npc_bummerl: usize,
+    pc_schneider: usize,
+    npc_schneider: usize,
+    deals: Vec<DealResult>,
+}
+
+fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}\n\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let start = Instant::now();
+    let results = match run(&options) {
+        Ok(results) => results,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let report = get_report(&options, results);
+    print_report(&report);
+    println!("Finished in {:.1?}", start.elapsed());
+
+    if let Some(path) = &options.csv {
+        if let Err(e) = std::fs::write(path, get_csv(&report.deals)) {
+            eprintln!("Could not write {}: {}", path, e);
+        }
+    }
+    if let Some(path) = &options.json {
+        let written = serde_json::to_string_pretty(&report)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(path, json).map_err(|e| e.to_string()));
+        if let Err(e) = written {
+            eprintln!("Could not write {}: {}", path, e);
+        }
+    }
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        pc: "normal".to_owned(),
+        npc: "normal".to_owned(),
+        matches: 100,
+        seed: rand::random(),
+        threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        csv: None,
+        json: None,
+    };
+
+    while let Some(arg) = args.next() {
+        if arg == "--help" || arg == "-h" {
+            println!("{}", USAGE);
+            std::process::exit(0);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("Missing value of {}", arg))?;
+        let number = || {
+            value
+                .parse::<u64>()
+                .map_err(|_| format!("{} must be a number", arg))
+        };
+        match arg.as_str() {
+            "--pc" => options.pc = value.to_lowercase(),
+            "--npc" => options.npc = value.to_lowercase(),
+            "--matches" => options.matches = number()? as usize,
+            "--seed" => options.seed = number()?,
+            "--threads" => options.threads = (number()? as usize).max(1),
+            "--csv" => options.csv = Some(value),
+            "--json" => options.json = Some(value),
+            _ => return Err(format!("Unknown option: {}", arg)),
+        }
+    }
+
+    // fail before the threads start
+    get_strategy(&options.pc, 0)?;
+    get_strategy(&options.npc, 0)?;
+    Ok(options)
+}
+
+/// Creates a strategy by its name and how likely it forgets a card
+fn get_strategy(name: &str, seed: u64) -> Result<(Box<dyn Strategy>, f64), String> {
+    match name {
+        "random" => Ok((Box::new(RandomStrategy::new(seed)), 0.0)),
+        "heuristic" => Ok((Box::new(HeuristicStrategy), 0.0)),
+        _ => {
+            let difficulty = EDifficulty::from_str(name)?;
+            Ok((difficulty.strategy(seed), difficulty.forget_rate()))
+        }
+    }
+}
+
+/// Plays all matches on several threads
+fn run(options: &Options) -> Result<Vec<MatchResult>, GameError> {
+    let threads = options.threads.min(options.matches).max(1);
+    let mut results: Vec<(usize, MatchResult)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|thread| {
+                scope.spawn(move || {
+                    (thread..options.matches)
+                        .step_by(threads)
+                        .map(|i| play_match(options, i).map(|r| (i, r)))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("a thread of the arena panicked"))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|r| r.into_iter().flatten().collect())
+    })?;
+
+    results.sort_by_key(|(i, _)| *i);
+    Ok(results.into_iter().map(|(_, r)| r).collect())
+}
+
+/// Plays games until the first Bummerl is decided
+fn play_match(options: &Options, index: usize) -> Result<MatchResult, GameError> {
+    let seed = mix_seed(options.seed, index as u64);
+    let mut seats = get_seats(options, seed);
+    let mut game_match = Match::with_seed(seed);
+    let mut deals = vec![];
+
+    for game in 0..MAX_GAMES {
+        game_match.next_game()?;
+        let result = seats.play(&mut game_match.game)?;
+        deals.push(DealResult {
+            game_match: index,
+            game,
+            seed: game_match.game.seed(),
+            dealer: game_match.game.dealer().ok_or(GameError::NotStarted)?,
+            winner: result.player,
+            game_points: result.game_points,
+        });
+
+        if let Some(bummerl) = game_match.update() {
+            return Ok(MatchResult {
+                deals,
+                bummerl: Some(bummerl),
+            });
+        }
+    }
+
+    Ok(MatchResult {
+        deals,
+        bummerl: None,
+    })
+}
+
+fn get_seats(options: &Options, seed: u64) -> Seats {
+    let (pc, pc_forget_rate) =
+        get_strategy(&options.pc, mix_seed(seed, 1)).expect("the names are checked");
+    let (npc, npc_forget_rate) =
+        get_strategy(&options.npc, mix_seed(seed, 2)).expect("the names are checked");
+
+    let mut seats = Seats::new(Some(pc), Some(npc));
+    seats.pc_tracker = CardTracker::new(EPlayer::PC, pc_forget_rate, mix_seed(seed, 3));
+    seats.npc_tracker = CardTracker::new(EPlayer::NPC, npc_forget_rate, mix_seed(seed, 4));
+    seats
+}
+
+fn get_report(options: &Options, results: Vec<MatchResult>) -> Report {
+    let deals: Vec<DealResult> = results.iter().flat_map(|r| r.deals.clone()).collect();
+    let bummerl: Vec<BummerlResult> = results.iter().filter_map(|r| r.bummerl).collect();
+
+    let pc_games = deals.iter().filter(|d| d.winner == EPlayer::PC).count();
+    let game_points: Vec<f64> = deals
+        .iter()
+        .map(|d| match d.winner {
+            EPlayer::PC => d.game_points as f64,
+            EPlayer::NPC => -(d.game_points as f64),
+        })
+        .collect();
+    let count_bummerl = |player: EPlayer, schneider: bool| {
+        bummerl
+            .iter()
+            .filter(|b| b.player == player && (b.schneider || !schneider))
+            .count()
+    };
+
+    Report {
+        pc: options.pc.clone(),
+        npc: options.npc.clone(),
+        seed: options.seed,
+        matches: options.matches,
+        games: deals.len(),
+        pc_game_rate: get_rate(pc_games, deals.len()),
+        pc_game_points: get_mean(&game_points),
+        pc_bummerl_rate: get_rate(count_bummerl(EPlayer::PC, false), bummerl.len()),
+        pc_bummerl: count_bummerl(EPlayer::PC, false),
+        npc_bummerl: count_bummerl(EPlayer::NPC, false),
+        pc_schneider: count_bummerl(EPlayer::PC, true),
+        npc_schneider: count_bummerl(EPlayer::NPC, true),
+        deals,
+    }
+}
+
+/// The share of successes with the 95% Wilson score interval
+fn get_rate(successes: usize, total: usize) -> Estimate {
+    if total == 0 {
+        return Estimate {
+            value: 0.0,
+            low: 0.0,
+            high: 1.0,
+        };
+    }
+
+    let z = 1.96;
+    let n = total as f64;
+    let p = successes as f64 / n;
+    let center = (p + z * z / (2.0 * n)) / (1.0 + z * z / n);
+    let margin = z / (1.0 + z * z / n) * (p * (1.0 - p) / n + z * z / (4.0 * n * n)).sqrt();
+    Estimate {
+        value: p,
+        low: center - margin,
+        high: center + margin,
+    }
+}
+
+/// The mean with its 95% confidence interval
+fn get_mean(values: &[f64]) -> Estimate {
+    if values.is_empty() {
+        return Estimate {
+            value: 0.0,
+            low: 0.0,
+            high: 0.0,
+        };
+    }
+
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0).max(1.0);
+    let margin = 1.96 * (variance / n).sqrt();
+    Estimate {
+        value: mean,
+        low: mean - margin,
+        high: mean + margin,
+    }
+}
+
+fn print_report(report: &Report) {
+    println!("PC: {} vs NPC: {}", report.pc, report.npc);
+    println!("Seed: {}", report.seed);
+    println!("Matches: {}, games: {}", report.matches, report.games);
+    println!(
+        "PC game win rate: {:.1}% ({:.1}% - {:.1}%)",
+        report.pc_game_rate.value * 100.0,
+        report.pc_game_rate.low * 100.0,
+        report.pc_game_rate.high * 100.0
+    );
+    println!(
+        "PC game points per game: {:+.3} ({:+.3} - {:+.3})",
+        report.pc_game_points.value, report.pc_game_points.low, report.pc_game_points.high
+    );
+    println!(
+        "PC Bummerl win rate: {:.1}% ({:.1}% - {:.1}%)",
+        report.pc_bummerl_rate.value * 100.0,
+        report.pc_bummerl_rate.low * 100.0,
+        report.pc_bummerl_rate.high * 100.0
+    );
+    println!(
+        "Bummerl: PC {} ({} Schneider), NPC {} ({} Schneider)",
+        report.pc_bummerl, report.pc_schneider, report.npc_bummerl, report.npc_schneider
+    );
+}
+
+fn get_csv(deals: &[DealResult]) -> String {
+    let mut csv = "match,game,seed,dealer,winner,game_points\n".to_owned();
+    for deal in deals {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{}\n",
+            deal.game_match, deal.game, deal.seed, deal.dealer, deal.winner, deal.game_points
+        ));
+    }
+    csv
+}
@@ -0,0 +1,352 @@
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::{get_opponent, mix_seed, EPlayer, Game, GameError, GameResult};
+
+/// The game points a player needs to win a Bummerl
+pub const BUMMERL_POINTS: usize = 7;
+
+/// The result of a finished Bummerl
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BummerlResult {
+    pub player: EPlayer,
+    /// the loser did not win a single game point
+    pub schneider: bool,
+}
+
+impl std::fmt::Display for BummerlResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.schneider {
+            write!(f, "{} wins a Schneider-Bummerl", self.player)
+        } else {
+            write!(f, "{} wins a Bummerl", self.player)
+        }
+    }
+}
+
+/// The standings before the result of a game was booked, taking back the game restores them
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct Standings {
+    player_points: usize,
+    npc_points: usize,
+    games: Vec<GameResult>,
+    bummerl: usize,
+}
+
+/// A match of several games. Each player counts down from 7 game points,
+/// the first to reach 0 wins the Bummerl and a new one starts.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Match {
+    pub game: Game,
+    /// the game points the PC still needs to win the Bummerl
+    pub player_points: usize,
+    /// the game points the NPC still needs to win the Bummerl
+    pub npc_points: usize,
+    /// the dealer of the current game
+    pub dealer: Option<EPlayer>,
+    /// the results of all games in the current Bummerl
+    pub games: Vec<GameResult>,
+    /// all finished Bummerl
+    pub bummerl: Vec<BummerlResult>,
+    /// the seed of the match, the first game uses it and the following games derive their seeds from it
+    pub seed: u64,
+    /// the number of games dealt in this match
+    pub game_count: u64,
+    /// casual play, moves can be taken back. It is set when the match starts.
+    pub casual: bool,
+    /// the standings before the current game was booked, if it is
+    booked: Option<Standings>,
+}
+
+impl Default for Match {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Match {
+    /// Creates a new [`Match`] with a random seed.
+    pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a new [`Match`] from a seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            game: Game::with_seed(seed),
+            player_points: BUMMERL_POINTS,
+            npc_points: BUMMERL_POINTS,
+            dealer: None,
+            games: vec![],
+            bummerl: vec![],
+            seed,
+            game_count: 0,
+            casual: false,
+            booked: None,
+        }
+    }
+
+    /// Creates a new [`Match`] that goes on with the game, e.g. an imported one.
+    /// The following games derive their seeds from the seed of the game.
+    pub fn with_game(game: Game) -> Self {
+        let mut game_match = Self::with_seed(game.seed());
+        game_match.dealer = game.dealer();
+        game_match.game_count = 1;
+        game_match.game = game;
+        game_match
+    }
+
+    /// Deals the next game of the match. The dealer alternates between games.
+    pub fn next_game(&mut self) -> Result<(), GameError> {
+        self.update();
+
+        let seed = if self.game_count == 0 {
+            self.seed
+        } else {
+            mix_seed(self.seed, self.game_count)
+        };
+        self.game = Game::with_seed(seed);
+        self.game_count += 1;
+
+        let dealer = match self.dealer {
+            Some(dealer) => get_opponent(dealer),
+            None => self.game.choose_dealer(),
+        };
+        self.dealer = Some(dealer);
+
+        self.booked = None;
+        self.game.play_with_dealer(dealer)
+    }
+
+    /// Takes back the last action of the player in the current game, see [`Game::undo`].
+    /// Only casual matches allow it, ranked games count as they were played.
+    /// The result of a finished game is taken back, even when it decided the Bummerl.
+    pub fn undo(&mut self, player: EPlayer) -> Result<(), GameError> {
+        if !self.casual {
+            return Err(GameError::UndoDisabled);
+        }
+        self.game.undo(player)?;
+
+        if let Some(standings) = self.booked.take() {
+            self.player_points = standings.player_points;
+            self.npc_points = standings.npc_points;
+            self.games = standings.games;
+            self.bummerl.truncate(standings.bummerl);
+        }
+        Ok(())
+    }
+
+    /// Books the result of the current game once it is finished.
+    /// Returns the result of the Bummerl if the game decided it.
+    pub fn update(&mut self) -> Option<BummerlResult> {
+        if self.booked.is_some() {
+            return None;
+        }
+        let result = self.game.winner()?;
+        self.booked = Some(Standings {
+            player_points: self.player_points,
+            npc_points: self.npc_points,
+            games: self.games.clone(),
+            bummerl: self.bummerl.len(),
+        });
+
+        self.games.push(result);
+        let points = match result.player {
+            EPlayer::PC => &mut self.player_points,
+            EPlayer::NPC => &mut self.npc_points,
+        };
+        *points = points.saturating_sub(result.game_points);
+        if *points > 0 {
+            return None;
+        }
+
+        // the Bummerl is decided, start the next one
+        let bummerl = BummerlResult {
+            player: result.player,
+            schneider: self.get_points(get_opponent(result.player)) == BUMMERL_POINTS,
+        };
+        info!("{}", bummerl);
+
+        self.bummerl.push(bummerl);
+        self.games.clear();
+        self.player_points = BUMMERL_POINTS;
+        self.npc_points = BUMMERL_POINTS;
+        Some(bummerl)
+    }
+
+    /// Gets the game points the player still needs to win the Bummerl
+    pub fn get_points(&self, player: EPlayer) -> usize {
+        match player {
+            EPlayer::PC => self.player_points,
+            EPlayer::NPC => self.npc_points,
+        }
+    }
+
+    /// Gets the number of Bummerl the player has won
+    pub fn get_bummerl(&self, player: EPlayer) -> usize {
+        self.bummerl.iter().filter(|b| b.player == player).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Action, HeuristicStrategy, Seats};
+
+    const SEED: u64 = 42;
+
+    /// Ends the current game with the result and books it
+    fn finish(
+        game_match: &mut Match,
+        player: EPlayer,
+        game_points: usize,
+    ) -> Option<BummerlResult> {
+        game_match.game.winner = Some(GameResult {
+            player,
+            game_points,
+        });
+        game_match.update()
+    }
+
+    #[test]
+    fn dealer_alternates() {
+        let mut game_match = Match::with_seed(SEED);
+        game_match.next_game().unwrap();
+        let dealer = game_match.dealer.unwrap();
+        assert_eq!(game_match.game.dealer(), Some(dealer));
+
+        for i in 1..4 {
+            finish(&mut game_match, EPlayer::PC, 1);
+            game_match.next_game().unwrap();
+            let expected = if i % 2 == 0 {
+                dealer
+            } else {
+                get_opponent(dealer)
+            };
+            assert_eq!(game_match.dealer, Some(expected));
+            assert_eq!(game_match.game.dealer(), Some(expected));
+        }
+    }
+
+    #[test]
+    fn bummerl_at_zero_points() {
+        let mut game_match = Match::with_seed(SEED);
+        game_match.next_game().unwrap();
+
+        assert_eq!(finish(&mut game_match, EPlayer::PC, 3), None);
+        game_match.next_game().unwrap();
+        assert_eq!(finish(&mut game_match, EPlayer::NPC, 1), None);
+        game_match.next_game().unwrap();
+        assert_eq!(finish(&mut game_match, EPlayer::PC, 3), None);
+        assert_eq!(game_match.get_points(EPlayer::PC), 1);
+        assert_eq!(game_match.get_points(EPlayer::NPC), 6);
+        assert_eq!(game_match.games.len(), 3);
+
+        // more game points than needed still end the Bummerl at 0
+        game_match.next_game().unwrap();
+        assert_eq!(
+            finish(&mut game_match, EPlayer::PC, 2),
+            Some(BummerlResult {
+                player: EPlayer::PC,
+                schneider: false
+            })
+        );
+
+        // the next Bummerl starts from 7 again
+        assert_eq!(game_match.get_points(EPlayer::PC), BUMMERL_POINTS);
+        assert_eq!(game_match.get_points(EPlayer::NPC), BUMMERL_POINTS);
+        assert!(game_match.games.is_empty());
+        assert_eq!(game_match.get_bummerl(EPlayer::PC), 1);
+        assert_eq!(game_match.get_bummerl(EPlayer::NPC), 0);
+    }
+
+    #[test]
+    fn schneider_when_the_loser_never_scored() {
+        let mut game_match = Match::with_seed(SEED);
+        for game_points in [3, 3, 1] {
+            game_match.next_game().unwrap();
+            finish(&mut game_match, EPlayer::NPC, game_points);
+        }
+
+        assert_eq!(
+            game_match.bummerl,
+            vec![BummerlResult {
+                player: EPlayer::NPC,
+                schneider: true
+            }]
+        );
+    }
+
+    #[test]
+    fn update_books_a_game_once() {
+        let mut game_match = Match::with_seed(SEED);
+        game_match.next_game().unwrap();
+        finish(&mut game_match, EPlayer::PC, 2);
+        assert_eq!(game_match.update(), None);
+        assert_eq!(game_match.update(), None);
+
+        // next_game books the finished game too
+        game_match.next_game().unwrap();
+        assert_eq!(game_match.games.len(), 1);
+        assert_eq!(game_match.get_points(EPlayer::PC), 5);
+    }
+
+    #[test]
+    fn undo_is_disabled_in_ranked_matches() {
+        let mut game_match = Match::with_seed(SEED);
+        game_match.next_game().unwrap();
+        let player = game_match.game.current_player().unwrap();
+        let card = game_match.game.legal_moves(player)[0].clone();
+        game_match.game.apply(Action::Play(card)).unwrap();
+
+        let actions = game_match.game.actions().to_vec();
+        assert_eq!(game_match.undo(player), Err(GameError::UndoDisabled));
+        assert_eq!(game_match.game.actions(), actions);
+
+        game_match.casual = true;
+        game_match.undo(player).unwrap();
+        assert!(game_match.game.actions().is_empty());
+    }
+
+    #[test]
+    fn undo_takes_back_the_result() {
+        let mut game_match = Match::with_seed(SEED);
+        game_match.casual = true;
+        let mut seats = Seats::new(
+            Some(Box::new(HeuristicStrategy)),
+            Some(Box::new(HeuristicStrategy)),
+        );
+
+        // every game is taken back once, until one decides the Bummerl
+        loop {
+            game_match.next_game().unwrap();
+            let standings = (
+                game_match.player_points,
+                game_match.npc_points,
+                game_match.games.clone(),
+            );
+            seats.play(&mut game_match.game).unwrap();
+            let bummerl = game_match.update();
+
+            game_match.undo(EPlayer::PC).unwrap();
+            assert!(game_match.game.winner().is_none());
+            assert_eq!(
+                (
+                    game_match.player_points,
+                    game_match.npc_points,
+                    game_match.games.clone()
+                ),
+                standings
+            );
+            if bummerl.is_some() {
+                assert!(game_match.bummerl.is_empty());
+                return;
+            }
+
+            // the game is played again and booked once more
+            seats.play(&mut game_match.game).unwrap();
+            assert_eq!(game_match.update(), None);
+            assert_eq!(game_match.games.len(), standings.2.len() + 1);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{EndgameStrategy, HeuristicStrategy, MonteCarloStrategy, RandomStrategy, Strategy};
+
+/// The sampled games per decision of Hard. The budgets count games, not time,
+/// so a seed gives the same decisions on every machine.
+const HARD_ITERATIONS: usize = 300;
+/// The sampled games per decision of Expert
+const EXPERT_ITERATIONS: usize = 2000;
+
+/// How strong the computer plays
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum EDifficulty {
+    /// plays random legal cards
+    Beginner,
+    /// plays by a few simple rules
+    #[default]
+    Normal,
+    /// searches by sampling the unseen cards
+    Hard,
+    /// searches longer, counts every card and plays the endgame perfectly
+    Expert,
+}
+
+impl std::fmt::Display for EDifficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EDifficulty::Beginner => write!(f, "Beginner"),
+            EDifficulty::Normal => write!(f, "Normal"),
+            EDifficulty::Hard => write!(f, "Hard"),
+            EDifficulty::Expert => write!(f, "Expert"),
+        }
+    }
+}
+
+impl std::str::FromStr for EDifficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        EDifficulty::ALL
+            .into_iter()
+            .find(|d| d.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown difficulty: {}", s))
+    }
+}
+
+impl EDifficulty {
+    pub const ALL: [EDifficulty; 4] = [
+        EDifficulty::Beginner,
+        EDifficulty::Normal,
+        EDifficulty::Hard,
+        EDifficulty::Expert,
+    ];
+
+    /// The chance that the computer forgets a card it has seen
+    pub fn forget_rate(&self) -> f64 {
+        match self {
+            EDifficulty::Beginner => 0.5,
+            EDifficulty::Normal => 0.2,
+            EDifficulty::Hard => 0.05,
+            EDifficulty::Expert => 0.0,
+        }
+    }
+
+    /// Creates the strategy of the difficulty, the seed drives its random decisions.
+    /// The search of Hard and Expert takes a moment, see [`Strategy::start`].
+    pub fn strategy(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            EDifficulty::Beginner => Box::new(RandomStrategy::new(seed)),
+            EDifficulty::Normal => Box::new(HeuristicStrategy),
+            EDifficulty::Hard => Box::new(MonteCarloStrategy::new(HARD_ITERATIONS, seed)),
+            EDifficulty::Expert => Box::new(EndgameStrategy::new(Box::new(
+                MonteCarloStrategy::new(EXPERT_ITERATIONS, seed),
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CardTracker, EPlayer, Game};
+
+    const SEED: u64 = 42;
+
+    #[test]
+    fn presets_build_their_strategy() {
+        let names: Vec<String> = EDifficulty::ALL
+            .iter()
+            .map(|d| d.strategy(SEED).name())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "Random",
+                "Heuristic",
+                "Monte Carlo",
+                "Monte Carlo + Endgame"
+            ]
+        );
+
+        let forget_rates: Vec<f64> = EDifficulty::ALL.iter().map(|d| d.forget_rate()).collect();
+        assert_eq!(forget_rates, [0.5, 0.2, 0.05, 0.0]);
+    }
+
+    #[test]
+    fn presets_decide_the_same_for_a_seed() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::PC).unwrap();
+        let view = game.view(EPlayer::NPC);
+        let mut tracker = CardTracker::new(EPlayer::NPC, 0.0, SEED);
+        for event in game.history() {
+            tracker.observe(event);
+        }
+
+        for difficulty in EDifficulty::ALL {
+            let action = difficulty
+                .strategy(SEED)
+                .choose_action(&view, &tracker)
+                .unwrap();
+            let again = difficulty
+                .strategy(SEED)
+                .choose_action(&view, &tracker)
+                .unwrap();
+            assert_eq!(action, again, "{}", difficulty);
+        }
+    }
+
+    #[test]
+    fn parse_the_names() {
+        for difficulty in EDifficulty::ALL {
+            assert_eq!(
+                difficulty.to_string().to_lowercase().parse(),
+                Ok(difficulty)
+            );
+        }
+        assert!("master".parse::<EDifficulty>().is_err());
+    }
+}
@@ -0,0 +1,217 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    ai::is_in_marriage, solve, wins, Action, Card, CardTracker, Decision, EValue, GameError,
+    MonteCarloStrategy, PlayerView,
+};
+
+/// A suggested action for a player with a short reason
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub action: Action,
+    pub reason: String,
+}
+
+/// The sampled games of a hint
+const HINT_ITERATIONS: usize = 1000;
+
+/// Asks the strongest strategy what it would do. Only what the player knows is used:
+/// the view and the cards the player remembers.
+pub fn get_hint(view: &PlayerView, tracker: &CardTracker, seed: u64) -> Result<Hint, GameError> {
+    HintSearch::new(view, tracker, seed)?.run()
+}
+
+/// A hint that is worked out a bit at a time, e.g. on a thread or a step per frame, see [`get_hint`]
+#[derive(Debug, Clone)]
+pub struct HintSearch {
+    view: PlayerView,
+    tracker: CardTracker,
+    decision: Decision,
+    /// the result of the best play, known when the endgame is solved
+    outcome: Option<String>,
+}
+
+impl HintSearch {
+    /// Starts the hint, the endgame is solved right away
+    pub fn new(view: &PlayerView, tracker: &CardTracker, seed: u64) -> Result<Self, GameError> {
+        if !tracker.knows_all_cards(view) {
+            let search = MonteCarloStrategy::new(HINT_ITERATIONS, seed).search(view, tracker)?;
+            return Ok(Self {
+                view: view.clone(),
+                tracker: tracker.clone(),
+                decision: Decision::Searching(Box::new(search)),
+                outcome: None,
+            });
+        }
+
+        let game = tracker.determinize(view, &mut StdRng::seed_from_u64(seed));
+        let solution = solve(&game)?;
+        let game_points = match solution.result.game_points {
+            1 => "1 game point".to_owned(),
+            n => format!("{} game points", n),
+        };
+        let outcome = if solution.result.player == view.player {
+            format!("wins {}", game_points)
+        } else {
+            format!("still loses {}", game_points)
+        };
+        Ok(Self {
+            view: view.clone(),
+            tracker: tracker.clone(),
+            decision: Decision::Done(solution.action),
+            outcome: Some(outcome),
+        })
+    }
+
+    /// Goes on with the hint. Returns the hint once it is found.
+    pub fn step(&mut self) -> Result<Option<Hint>, GameError> {
+        let Some(action) = self.decision.step()? else {
+            return Ok(None);
+        };
+        let reason = get_reason(&self.view, &self.tracker, &action);
+        Ok(Some(Hint {
+            reason: match &self.outcome {
+                Some(outcome) => format!("{}, best play {}", reason, outcome),
+                None => reason,
+            },
+            action,
+        }))
+    }
+
+    /// Works out the rest of the hint
+    pub fn run(mut self) -> Result<Hint, GameError> {
+        loop {
+            if let Some(hint) = self.step()? {
+                return Ok(hint);
+            }
+        }
+    }
+}
+
+/// Explains the action in a few words
+fn get_reason(view: &PlayerView, tracker: &CardTracker, action: &Action) -> String {
+    match action {
+        Action::ClaimOut => format!("you have {} points", view.points),
+        Action::ExchangeTrump => "take the trump card".to_owned(),
+        Action::Close => "close the talon, your cards are strong enough".to_owned(),
+        Action::DeclareMarriage(suit) => {
+            format!(
+                "declare the marriage for {}",
+                view.get_marriage_value(*suit)
+            )
+        }
+        Action::Continue => "continue".to_owned(),
+        Action::Play(card) => match &view.trick.0 {
+            Some(lead) => get_follow_reason(view, card, lead),
+            None => get_lead_reason(view, tracker, card),
+        },
+    }
+}
+
+fn get_follow_reason(view: &PlayerView, card: &Card, lead: &Card) -> String {
+    if wins(card, lead, view.trump_suit) {
+        if view.is_trump(card) && !view.is_trump(lead) {
+            format!("trump to win the {}", lead.value)
+        } else {
+            format!("win the {}", lead.value)
+        }
+    } else if !view
+        .legal_moves()
+        .iter()
+        .any(|c| wins(c, lead, view.trump_suit))
+    {
+        "the trick can't be won, give away little".to_owned()
+    } else if has_marriage(view) && !is_in_marriage(view, card) {
+        "save the marriage".to_owned()
+    } else {
+        "the trick isn't worth it, keep the better cards".to_owned()
+    }
+}
+
+fn get_lead_reason(view: &PlayerView, tracker: &CardTracker, card: &Card) -> String {
+    if view.marriage_lead.is_some() {
+        return "lead a card of the marriage".to_owned();
+    }
+    let candidates = tracker.get_opponent_candidates(view);
+    let can_be_beaten = candidates
+        .iter()
+        .any(|c| c.suit == card.suit && c.value > card.value);
+    if !can_be_beaten && view.must_follow_suit() {
+        format!("no card left can beat the {}", card.value)
+    } else if has_marriage(view) && !is_in_marriage(view, card) {
+        "save the marriage".to_owned()
+    } else if view.is_trump(card) {
+        "pull the trumps of the opponent".to_owned()
+    } else if card.value >= EValue::X {
+        format!("cash in the {}", card.value)
+    } else {
+        "lead a cheap card and keep the trumps".to_owned()
+    }
+}
+
+fn has_marriage(view: &PlayerView) -> bool {
+    view.hand.iter().any(|c| is_in_marriage(view, c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EPlayer, Game};
+
+    const SEED: u64 = 42;
+
+    /// Deals a game, the PC is on lead and remembers what it has seen
+    fn deal(seed: u64) -> (Game, CardTracker) {
+        let mut game = Game::with_seed(seed);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, seed);
+        for event in game.drain_events() {
+            tracker.observe(&event);
+        }
+        (game, tracker)
+    }
+
+    #[test]
+    fn hint_is_legal() {
+        for seed in 0..3 {
+            let (game, tracker) = deal(seed);
+            let view = game.view(EPlayer::PC);
+            let hint = get_hint(&view, &tracker, SEED).unwrap();
+            assert!(view.legal_actions.contains(&hint.action), "{:?}", hint);
+            assert!(!hint.reason.is_empty());
+        }
+    }
+
+    #[test]
+    fn hint_ignores_the_hidden_cards() {
+        let (game, tracker) = deal(SEED);
+        let hint = get_hint(&game.view(EPlayer::PC), &tracker, SEED).unwrap();
+
+        // the opponent holds other cards of the talon
+        let mut other = game.clone();
+        for i in 0..2 {
+            std::mem::swap(&mut other.npc_hand[i], &mut other.talon[i]);
+        }
+        assert_ne!(other.npc_hand, game.npc_hand);
+        assert_eq!(
+            get_hint(&other.view(EPlayer::PC), &tracker, SEED).unwrap(),
+            hint
+        );
+    }
+
+    #[test]
+    fn hint_in_steps_is_the_same() {
+        let (game, tracker) = deal(SEED);
+        let view = game.view(EPlayer::PC);
+        let mut search = HintSearch::new(&view, &tracker, SEED).unwrap();
+        let mut steps = 1;
+        let hint = loop {
+            if let Some(hint) = search.step().unwrap() {
+                break hint;
+            }
+            steps += 1;
+        };
+        assert!(steps > 1);
+        assert_eq!(hint, get_hint(&view, &tracker, SEED).unwrap());
+    }
+}
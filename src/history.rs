@@ -0,0 +1,55 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{Card, EPlayer, Game, GameEvent};
+
+/// A trick of a [`Game`], built from its history
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Trick {
+    /// the cards in the order they were played
+    pub cards: Vec<(EPlayer, Card)>,
+    /// the player who took the trick once it is evaluated
+    pub winner: Option<EPlayer>,
+}
+
+impl Trick {
+    /// The player who led the trick
+    pub fn leader(&self) -> Option<EPlayer> {
+        self.cards.first().map(|(player, _)| *player)
+    }
+}
+
+impl Game {
+    /// Gets everything that happened since the deal in order, including the events that were already drained.
+    /// The cards dealt to the opponent are in it, check [`GameEvent::is_visible_to`] before showing them.
+    pub fn history(&self) -> &[GameEvent] {
+        &self.history
+    }
+
+    /// Gets the tricks in the order they were played, the last one may still be running
+    pub fn tricks(&self) -> Vec<Trick> {
+        get_tricks(&self.history)
+    }
+}
+
+/// Splits the history of a game into its tricks
+pub fn get_tricks(history: &[GameEvent]) -> Vec<Trick> {
+    let mut tricks: Vec<Trick> = vec![];
+    for event in history {
+        match event {
+            GameEvent::CardPlayed { player, card } => match tricks.last_mut() {
+                Some(trick) if trick.winner.is_none() => trick.cards.push((*player, card.clone())),
+                _ => tricks.push(Trick {
+                    cards: vec![(*player, card.clone())],
+                    winner: None,
+                }),
+            },
+            GameEvent::TrickWon { player, .. } => {
+                if let Some(trick) = tricks.last_mut() {
+                    trick.winner = Some(*player);
+                }
+            }
+            _ => {}
+        }
+    }
+    tricks
+}
@@ -1,15 +1,39 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
+mod ai;
+mod analysis;
 mod app;
+mod bummerl;
+mod difficulty;
+mod hint;
+mod history;
+mod notation;
+mod replay;
+mod search;
+mod solver;
+mod tracker;
+mod view;
 
+pub use ai::{heuristic_action, Decision, HeuristicStrategy, RandomStrategy, Seats, Strategy};
+pub use analysis::{Analyzer, MoveReview, TrickReview};
 pub use app::TemplateApp;
-use egui_notify::Toasts;
-
+pub use bummerl::{BummerlResult, Match, BUMMERL_POINTS};
+pub use difficulty::EDifficulty;
+pub use hint::{get_hint, Hint, HintSearch};
+pub use history::{get_tricks, Trick};
+pub use notation::NotationError;
+pub use replay::Replay;
+pub use search::{simulate, MonteCarloStrategy, Search};
+pub use solver::{evaluate, solve, EndgameStrategy, Solution};
+use std::collections::VecDeque;
 use std::fmt::Display;
+pub use tracker::CardTracker;
+pub use view::PlayerView;
 
 use log::{debug, info, warn};
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
 
 // enums
@@ -49,7 +73,7 @@ impl Display for EValue {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum ESuit {
     Hearts,
     Bells,
@@ -57,12 +81,166 @@ pub enum ESuit {
     Leaves,
 }
 
+/// Errors of the rules engine. An action that returns an error does not change the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GameError {
+    /// the game has not been started yet
+    NotStarted,
+    /// the game has already been started
+    AlreadyStarted,
+    /// it is not the turn of the player
+    NotYourTurn,
+    /// the player does not hold the card
+    CardNotInHand,
+    /// the action is not allowed by the rules right now
+    IllegalMove,
+    /// there is no card left to draw
+    TalonEmpty,
+    /// the game is already over
+    GameOver,
+    /// the player has taken no action that can be taken back
+    NothingToUndo,
+    /// moves can only be taken back in casual play
+    UndoDisabled,
+    /// the game is still running
+    NotFinished,
+}
+
+impl Display for GameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameError::NotStarted => write!(f, "The game has not been started"),
+            GameError::AlreadyStarted => write!(f, "The game has already been started"),
+            GameError::NotYourTurn => write!(f, "It is not your turn"),
+            GameError::CardNotInHand => write!(f, "The card is not in the hand"),
+            GameError::IllegalMove => write!(f, "This is not allowed now"),
+            GameError::TalonEmpty => write!(f, "There is no card left to draw"),
+            GameError::GameOver => write!(f, "The game is over"),
+            GameError::NothingToUndo => write!(f, "There is nothing to undo"),
+            GameError::UndoDisabled => write!(f, "Moves can only be taken back in casual play"),
+            GameError::NotFinished => write!(f, "The game is not finished yet"),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Everything that happens in a [`Game`]. Events are queued and can be taken with [`Game::drain_events`],
+/// [`Game::history`] keeps all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum GameEvent {
+    GameStarted {
+        dealer: EPlayer,
+        /// the seed of the game, it lets the players vary their random decisions between games.
+        /// It reveals the order of the talon, see [`Game::seed`].
+        seed: u64,
+    },
+    CardDealt {
+        player: EPlayer,
+        card: Card,
+    },
+    TrumpRevealed(Card),
+    CardPlayed {
+        player: EPlayer,
+        card: Card,
+    },
+    TrickWon {
+        player: EPlayer,
+        points: usize,
+    },
+    MarriageDeclared {
+        player: EPlayer,
+        suit: ESuit,
+        points: usize,
+    },
+    TrumpExchanged {
+        player: EPlayer,
+        card: Card,
+    },
+    TalonClosed {
+        player: EPlayer,
+    },
+    OutClaimed {
+        player: EPlayer,
+        points: usize,
+    },
+    GameOver(GameResult),
+}
+
+impl Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameEvent::GameStarted { dealer, .. } => {
+                write!(f, "A new game has started. The dealer is: {}.", dealer)
+            }
+            GameEvent::CardDealt { player, card } => write!(f, "{} drew {}", player, card),
+            GameEvent::TrumpRevealed(card) => write!(f, "Trump card is: {}.", card),
+            GameEvent::CardPlayed { player, card } => write!(f, "{} played {}", player, card),
+            GameEvent::TrickWon { player, points } => {
+                write!(f, "{} won this trick and has {} points", player, points)
+            }
+            GameEvent::MarriageDeclared {
+                player,
+                suit,
+                points,
+            } => write!(f, "{} declared a marriage in {} ({})", player, suit, points),
+            GameEvent::TrumpExchanged { player, .. } => {
+                write!(f, "{} exchanged the trump Unter", player)
+            }
+            GameEvent::TalonClosed { player } => write!(f, "{} closed the talon", player),
+            GameEvent::OutClaimed { player, points } => {
+                write!(f, "{} claimed 66 with {} points", player, points)
+            }
+            GameEvent::GameOver(result) => write!(f, "The game ended. {}", result),
+        }
+    }
+}
+
+impl GameEvent {
+    /// Checks if the player may see the event, only the cards drawn by the player are hidden from the opponent
+    pub fn is_visible_to(&self, player: EPlayer) -> bool {
+        match self {
+            GameEvent::CardDealt { player: p, .. } => *p == player,
+            _ => true,
+        }
+    }
+}
+
+/// The phase of a [`Game`]
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub enum EGameState {
+    /// the game has not been started
     None,
     PlayerTurn,
     NpcTurn,
+    /// the trick is complete and must be evaluated with [`Action::Continue`]
     Evaluate,
+    Finished,
+}
+
+/// Everything a player can do in a [`Game`], applied with [`Game::apply`]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Action {
+    Play(Card),
+    DeclareMarriage(ESuit),
+    ExchangeTrump,
+    Close,
+    ClaimOut,
+    /// evaluates a complete trick
+    Continue,
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Action::Play(card) => write!(f, "play {}", card),
+            Action::DeclareMarriage(suit) => write!(f, "declare a marriage in {}", suit),
+            Action::ExchangeTrump => write!(f, "exchange the trump Unter"),
+            Action::Close => write!(f, "close the talon"),
+            Action::ClaimOut => write!(f, "claim 66"),
+            Action::Continue => write!(f, "continue"),
+        }
+    }
 }
 
 impl Display for ESuit {
@@ -78,7 +256,14 @@ impl Display for ESuit {
 
 // structs
 
-#[derive(Debug, Deserialize, Serialize, Clone)]
+/// Optional rules that differ between variants of Schnapsen
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Variant {
+    /// the trump Unter may only be exchanged by a player who has already taken a trick
+    pub exchange_needs_trick: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub struct Card {
     pub suit: ESuit,
     pub value: EValue,
@@ -90,103 +275,246 @@ impl Display for Card {
     }
 }
 
+/// The result of a finished game: the winner and the awarded game points
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct GameResult {
+    pub player: EPlayer,
+    pub game_points: usize,
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} wins {} game points", self.player, self.game_points)
+    }
+}
+
 impl Card {
     pub fn new(suit: ESuit, value: EValue) -> Self {
         Self { suit, value }
     }
 }
 
-#[derive(Debug)]
+/// A game of Schnapsen. The cards are hidden from the players,
+/// use [`Game::view`] to get what a player may know.
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Game {
-    pub trump_suit: Option<ESuit>,
-    pub trump_card: Option<Card>,
-    pub talon: Vec<Card>,
-    pub trick: (Option<Card>, Option<Card>),
-    pub player_stack: Vec<Card>,
-    pub player_hand: Vec<Card>,
-    pub npc_stack: Vec<Card>,
-    pub npc_hand: Vec<Card>,
+    pub(crate) trump_suit: Option<ESuit>,
+    pub(crate) trump_card: Option<Card>,
+    pub(crate) talon: Vec<Card>,
+    pub(crate) trick: (Option<Card>, Option<Card>),
+    pub(crate) player_stack: Vec<Card>,
+    pub(crate) player_hand: Vec<Card>,
+    pub(crate) npc_stack: Vec<Card>,
+    pub(crate) npc_hand: Vec<Card>,
+    pub(crate) marriages: Vec<(EPlayer, ESuit)>,
+    /// cards that were shown to both players before they went into a hand
+    pub(crate) shown: Vec<(EPlayer, Card)>,
+    pub variant: Variant,
     // meta
-    pub forehand: Option<EPlayer>,
-    pub winner: Option<EPlayer>,
-    state: Option<EGameState>,
-    last_turn_time: f64,
+    pub(crate) forehand: Option<EPlayer>,
+    pub(crate) winner: Option<GameResult>,
+    /// the player who closed the talon
+    pub(crate) closed_by: Option<EPlayer>,
+    /// the points of the opponent of the closing player at the time of closing
+    pub(crate) closed_points: usize,
+    /// the suit of a marriage that was just declared, one of its cards must be led
+    pub(crate) marriage_lead: Option<ESuit>,
+    /// the seed of the game, drives shuffling, choosing the dealer and the AI
+    seed: u64,
+    dealer: Option<EPlayer>,
+    /// all actions applied since the deal, they replay the game together with the seed and the dealer
+    actions: Vec<Action>,
+    rng_draws: u64,
+    state: EGameState,
+    #[serde(skip)]
+    events: VecDeque<GameEvent>,
+    /// all events since the deal, they stay when the events are drained
+    pub(crate) history: Vec<GameEvent>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Game {
+    /// Creates a new [`Game`] with a random seed.
     pub fn new() -> Self {
+        Self::with_seed(rand::random())
+    }
+
+    /// Creates a new [`Game`] from a seed. Games with the same seed and the same moves are identical.
+    pub fn with_seed(seed: u64) -> Self {
         Self {
             trump_card: None,
             trump_suit: None,
-            talon: get_deck_shuffled(),
+            talon: get_deck_shuffled(&mut StdRng::seed_from_u64(seed)),
             trick: (None, None),
             player_stack: vec![],
             player_hand: vec![],
             npc_stack: vec![],
             npc_hand: vec![],
+            marriages: vec![],
+            shown: vec![],
+            variant: Variant::default(),
             forehand: None,
             winner: None,
-            state: None,
-            last_turn_time: -1.0,
+            closed_by: None,
+            closed_points: 0,
+            marriage_lead: None,
+            seed,
+            dealer: None,
+            actions: vec![],
+            rng_draws: 0,
+            state: EGameState::None,
+            events: VecDeque::new(),
+            history: vec![],
         }
     }
 
-    /// Starts this [`Game`].
-    pub fn play(&mut self, toasts: &mut Toasts, time: f64) {
-        debug!("A new game has started.");
-        toasts.info("A new game has started.");
+    /// The seed of the game. It reveals the order of the talon, don't show it to the players before the game is over.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The dealer of the game once it is dealt
+    pub fn dealer(&self) -> Option<EPlayer> {
+        self.dealer
+    }
+
+    /// Gets all actions applied since the deal
+    pub fn actions(&self) -> &[Action] {
+        &self.actions
+    }
+
+    /// Deals a game of the variant from the seed and applies the actions
+    pub fn replay(
+        seed: u64,
+        dealer: EPlayer,
+        variant: Variant,
+        actions: &[Action],
+    ) -> Result<Self, GameError> {
+        let mut game = Self::with_seed(seed);
+        game.variant = variant;
+        game.play_with_dealer(dealer)?;
+        for action in actions {
+            game.apply(action.clone())?;
+        }
+        Ok(game)
+    }
+
+    /// Takes back the last action of the player and all actions after it, e.g. the reply of the opponent.
+    /// The game is dealt again from the seed and replayed, so the cards and all random decisions are
+    /// the same as before. The events of the replay are queued from [`GameEvent::GameStarted`] on,
+    /// so observers like a [`CardTracker`] can learn everything again. The action that ended the game
+    /// can be taken back too, [`Match::undo`] then takes back the result.
+    pub fn undo(&mut self, player: EPlayer) -> Result<(), GameError> {
+        let dealer = self.dealer.ok_or(GameError::NotStarted)?;
+
+        // find the last action of the player
+        let mut replay = Self::replay(self.seed, dealer, self.variant.clone(), &[])?;
+        let mut last = None;
+        for (i, action) in self.actions.iter().enumerate() {
+            if replay.is_turn_of(player) {
+                last = Some(i);
+            }
+            replay.apply(action.clone())?;
+        }
+        let last = last.ok_or(GameError::NothingToUndo)?;
+
+        *self = Self::replay(
+            self.seed,
+            dealer,
+            self.variant.clone(),
+            &self.actions[..last],
+        )?;
+        Ok(())
+    }
 
-        // determine who is dealer
-        let mut dealer: EPlayer = EPlayer::PC;
-        self.set_state(EGameState::NpcTurn, time);
-        if rand::random() {
-            // generates a boolean
-            dealer = EPlayer::NPC;
-            self.set_state(EGameState::PlayerTurn, time);
+    /// The result of the game once it is finished
+    pub fn winner(&self) -> Option<GameResult> {
+        self.winner
+    }
+
+    /// Starts this [`Game`] with a random dealer.
+    pub fn play(&mut self) -> Result<(), GameError> {
+        let dealer = self.choose_dealer();
+        self.play_with_dealer(dealer)
+    }
+
+    /// Chooses a random dealer
+    pub fn choose_dealer(&mut self) -> EPlayer {
+        if self.rng().gen() {
+            EPlayer::NPC
+        } else {
+            EPlayer::PC
         }
+    }
+
+    /// Creates a random number generator from the seed of the game.
+    /// Every call gives a new generator, so all random decisions are reproduced when a game is replayed.
+    pub fn rng(&mut self) -> StdRng {
+        self.rng_draws += 1;
+        StdRng::seed_from_u64(mix_seed(self.seed, self.rng_draws))
+    }
+
+    /// Starts this [`Game`] with the given dealer.
+    pub fn play_with_dealer(&mut self, dealer: EPlayer) -> Result<(), GameError> {
+        if self.state != EGameState::None || self.talon.len() != 20 {
+            return Err(GameError::AlreadyStarted);
+        }
+        debug!("A new game has started.");
+
         let first_player = get_opponent(dealer);
+        self.dealer = Some(dealer);
+        self.state = get_turn_state(first_player);
         debug!("The dealer is: {}.", dealer);
-        toasts.info(format!("The dealer is: {}.", dealer));
+        self.emit(GameEvent::GameStarted {
+            dealer,
+            seed: self.seed,
+        });
 
         // deal cards
-        self.deal_card(first_player);
-        self.deal_card(first_player);
-        self.deal_card(first_player);
-        self.deal_card(dealer);
-        self.deal_card(dealer);
-        self.deal_card(dealer);
+        self.deal_card(first_player)?;
+        self.deal_card(first_player)?;
+        self.deal_card(first_player)?;
+        self.deal_card(dealer)?;
+        self.deal_card(dealer)?;
+        self.deal_card(dealer)?;
 
         self.trump_card = self.talon.pop();
-        if let Some(c) = &self.trump_card {
-            self.trump_suit = Some(c.suit.clone());
+        if let Some(c) = self.trump_card.clone() {
+            self.trump_suit = Some(c.suit);
 
             debug!("Trump card is: {}.", c);
-            //toasts.info(format!("Trump card is: {}.", c));
+            self.emit(GameEvent::TrumpRevealed(c));
         }
 
-        self.deal_card(first_player);
-        self.deal_card(first_player);
-        self.deal_card(dealer);
-        self.deal_card(dealer);
+        self.deal_card(first_player)?;
+        self.deal_card(first_player)?;
+        self.deal_card(dealer)?;
+        self.deal_card(dealer)?;
 
-        // start first turn
-        if first_player == EPlayer::NPC {
-            self.do_turn(toasts, time);
-        }
+        Ok(())
     }
 
     /// Deals a card from the talon, or the trump card if the talon is empty
-    ///
-    /// # Panics
-    ///
-    /// Panics if trump card is None
-    fn deal_card(&mut self, to: EPlayer) {
-        let card = if self.talon.is_empty() {
-            self.trump_card.take().unwrap()
-        } else {
-            self.talon.pop().unwrap()
+    fn deal_card(&mut self, to: EPlayer) -> Result<(), GameError> {
+        let card = match self.talon.pop() {
+            Some(card) => card,
+            None => {
+                // the last card is the face-up trump card
+                let card = self.trump_card.take().ok_or(GameError::TalonEmpty)?;
+                self.shown.push((to, card.clone()));
+                card
+            }
         };
 
+        self.emit(GameEvent::CardDealt {
+            player: to,
+            card: card.clone(),
+        });
         match to {
             EPlayer::PC => {
                 self.player_hand.push(card);
@@ -195,61 +523,282 @@ impl Game {
                 self.npc_hand.push(card);
             }
         }
+        Ok(())
     }
 
-    /// A turn in the game. consumes the current game state
-    ///
-    /// # Panics
-    ///
-    /// Panics if .
-    pub fn do_turn(&mut self, toasts: &mut Toasts, time: f64) {
-        if let Some(state) = self.state.take() {
-            match state {
-                EGameState::None => {}
-                EGameState::PlayerTurn => {}
-                EGameState::NpcTurn => {
-                    let card = self.ai_choose_card();
-                    self.play_card(card, EPlayer::NPC, time);
-                }
-                EGameState::Evaluate => self.evaluate(toasts, time),
-            }
+    fn emit(&mut self, event: GameEvent) {
+        self.history.push(event.clone());
+        self.events.push_back(event);
+    }
+
+    /// Takes all events that happened since the last call
+    pub fn drain_events(&mut self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.events.drain(..)
+    }
+
+    /// Applies the action of the current player, or evaluates a complete trick with [`Action::Continue`]
+    pub fn apply(&mut self, action: Action) -> Result<(), GameError> {
+        match self.state {
+            EGameState::None => return Err(GameError::NotStarted),
+            EGameState::Finished => return Err(GameError::GameOver),
+            _ => {}
         }
+        self.apply_action(action.clone())?;
+        self.actions.push(action);
+        Ok(())
     }
 
-    /// Plays a card and evaluates the trick
-    ///
-    /// # Panics
-    ///
-    /// Panics if not forehand and no card in trick
-    pub fn play_card(&mut self, card: Card, player: EPlayer, time: f64) {
+    fn apply_action(&mut self, action: Action) -> Result<(), GameError> {
+        if action == Action::Continue {
+            return self.evaluate();
+        }
+
+        let player = self.current_player().ok_or(GameError::NotYourTurn)?;
+        match action {
+            Action::Play(card) => self.play_card(card, player),
+            Action::DeclareMarriage(suit) => self.declare_marriage(player, suit),
+            Action::ExchangeTrump => self.exchange_trump(player),
+            Action::Close => self.close_talon(player),
+            Action::ClaimOut => self.claim_out(player).map(|_| ()),
+            Action::Continue => unreachable!(),
+        }
+    }
+
+    /// Gets the phase of the game
+    pub fn phase(&self) -> EGameState {
+        self.state.clone()
+    }
+
+    /// Gets the player who has to act now, if any
+    pub fn current_player(&self) -> Option<EPlayer> {
+        match self.state {
+            EGameState::PlayerTurn => Some(EPlayer::PC),
+            EGameState::NpcTurn => Some(EPlayer::NPC),
+            _ => None,
+        }
+    }
+
+    /// Gets all actions that can be applied now
+    pub fn legal_actions(&self) -> Vec<Action> {
+        if self.state == EGameState::Evaluate {
+            return vec![Action::Continue];
+        }
+        let Some(player) = self.current_player() else {
+            return vec![];
+        };
+
+        let mut actions = vec![];
+        if self.can_claim_out(player) {
+            actions.push(Action::ClaimOut);
+        }
+        if self.can_exchange_trump(player) {
+            actions.push(Action::ExchangeTrump);
+        }
+        if self.can_close_talon(player) {
+            actions.push(Action::Close);
+        }
+        for suit in self.get_marriages(player) {
+            actions.push(Action::DeclareMarriage(suit));
+        }
+        for card in self.legal_moves(player) {
+            actions.push(Action::Play(card));
+        }
+        actions
+    }
+
+    /// Checks if it is the turn of the player
+    pub fn is_turn_of(&self, player: EPlayer) -> bool {
+        self.current_player() == Some(player)
+    }
+
+    /// Checks that the player may act now
+    fn check_turn(&self, player: EPlayer) -> Result<(), GameError> {
+        match self.state {
+            EGameState::None => Err(GameError::NotStarted),
+            EGameState::Finished => Err(GameError::GameOver),
+            _ if !self.is_turn_of(player) => Err(GameError::NotYourTurn),
+            _ => Ok(()),
+        }
+    }
+
+    /// Plays a card from the hand of the player
+    fn play_card(&mut self, card: Card, player: EPlayer) -> Result<(), GameError> {
+        self.check_turn(player)?;
         let is_forehand = self.trick.0.is_none();
 
+        let hand = match player {
+            EPlayer::PC => &self.player_hand,
+            EPlayer::NPC => &self.npc_hand,
+        };
+        let Some(index) = hand.iter().position(|c| *c == card) else {
+            warn!("{} does not hold {}", player, card);
+            return Err(GameError::CardNotInHand);
+        };
+        if !self.legal_moves(player).contains(&card) {
+            warn!("{} can't play {}", player, card);
+            return Err(GameError::IllegalMove);
+        }
+
+        match player {
+            EPlayer::PC => self.player_hand.remove(index),
+            EPlayer::NPC => self.npc_hand.remove(index),
+        };
+        self.marriage_lead = None;
+        self.emit(GameEvent::CardPlayed {
+            player,
+            card: card.clone(),
+        });
+
         if is_forehand {
             self.forehand = Some(player);
             self.trick.0 = Some(card);
 
             // end turn and go to other player
-            match player {
-                EPlayer::PC => self.set_state(EGameState::NpcTurn, time),
-                EPlayer::NPC => self.set_state(EGameState::PlayerTurn, time),
-            }
+            self.state = get_turn_state(get_opponent(player));
         } else {
             self.trick.1 = Some(card);
 
             // end turn and go to evaluate
-            self.set_state(EGameState::Evaluate, time);
+            self.state = EGameState::Evaluate;
         }
+
+        Ok(())
     }
 
-    fn evaluate(&mut self, toasts: &mut Toasts, time: f64) {
-        // check if backhand wins
-        let backhand_wins = wins(
-            self.trick.1.as_ref().unwrap(),
-            self.trick.0.as_ref().unwrap(),
-            self.trump_suit.clone().unwrap(),
+    /// Gets the cards the player may play now.
+    ///
+    /// The player on lead may play any card, unless a marriage was declared.
+    /// Once the talon is exhausted or closed the second player must follow suit
+    /// and head the trick if able, and must trump if void in the suit led.
+    pub fn legal_moves(&self, player: EPlayer) -> Vec<Card> {
+        let hand = match player {
+            EPlayer::PC => &self.player_hand,
+            EPlayer::NPC => &self.npc_hand,
+        };
+
+        let Some(lead) = &self.trick.0 else {
+            // a declared marriage forces the lead
+            if let Some(suit) = self.marriage_lead {
+                return hand
+                    .iter()
+                    .filter(|c| is_marriage_card(c, suit))
+                    .cloned()
+                    .collect();
+            }
+            return hand.clone();
+        };
+        if !self.must_follow_suit() {
+            return hand.clone();
+        }
+
+        // follow suit and head the trick if able
+        let same_suit: Vec<Card> = hand
+            .iter()
+            .filter(|c| c.suit == lead.suit)
+            .cloned()
+            .collect();
+        if !same_suit.is_empty() {
+            let higher: Vec<Card> = same_suit
+                .iter()
+                .filter(|c| c.value > lead.value)
+                .cloned()
+                .collect();
+            return if higher.is_empty() { same_suit } else { higher };
+        }
+
+        // trump if void
+        let trumps: Vec<Card> = hand
+            .iter()
+            .filter(|c| Some(c.suit) == self.trump_suit)
+            .cloned()
+            .collect();
+        if !trumps.is_empty() {
+            return trumps;
+        }
+
+        hand.clone()
+    }
+
+    /// Checks if the player holds King and Ober of the suit and may declare the marriage
+    pub fn can_declare_marriage(&self, player: EPlayer, suit: ESuit) -> bool {
+        // only the player on lead can declare a marriage
+        if self.trick.0.is_some() || self.marriage_lead.is_some() || self.winner.is_some() {
+            return false;
+        }
+        if self.marriages.iter().any(|(_, s)| *s == suit) {
+            return false;
+        }
+
+        let hand = match player {
+            EPlayer::PC => &self.player_hand,
+            EPlayer::NPC => &self.npc_hand,
+        };
+        hand.contains(&Card::new(suit, EValue::King))
+            && hand.contains(&Card::new(suit, EValue::Ober))
+    }
+
+    /// Gets all marriages the player could declare now
+    pub fn get_marriages(&self, player: EPlayer) -> Vec<ESuit> {
+        [ESuit::Hearts, ESuit::Bells, ESuit::Acorns, ESuit::Leaves]
+            .into_iter()
+            .filter(|s| self.can_declare_marriage(player, *s))
+            .collect()
+    }
+
+    /// Declares a marriage of King and Ober. One of the two cards must be led next.
+    fn declare_marriage(&mut self, player: EPlayer, suit: ESuit) -> Result<(), GameError> {
+        self.check_turn(player)?;
+        let hand = match player {
+            EPlayer::PC => &self.player_hand,
+            EPlayer::NPC => &self.npc_hand,
+        };
+        if !hand.contains(&Card::new(suit, EValue::King))
+            || !hand.contains(&Card::new(suit, EValue::Ober))
+        {
+            return Err(GameError::CardNotInHand);
+        }
+        if !self.can_declare_marriage(player, suit) {
+            warn!("{} can't declare a marriage in {}", player, suit);
+            return Err(GameError::IllegalMove);
+        }
+
+        self.marriages.push((player, suit));
+        self.marriage_lead = Some(suit);
+        self.shown.push((player, Card::new(suit, EValue::King)));
+        self.shown.push((player, Card::new(suit, EValue::Ober)));
+
+        let points = self.get_marriage_value(suit);
+        debug!(
+            "{} declared a marriage in {} worth {}",
+            player, suit, points
         );
+        self.emit(GameEvent::MarriageDeclared {
+            player,
+            suit,
+            points,
+        });
+        Ok(())
+    }
+
+    /// The value of a marriage in the suit, see [`get_marriage_value`]
+    pub fn get_marriage_value(&self, suit: ESuit) -> usize {
+        get_marriage_value(suit, self.trump_suit)
+    }
+
+    /// Evaluates a complete trick, the winner draws first and leads the next trick
+    fn evaluate(&mut self) -> Result<(), GameError> {
+        if self.state != EGameState::Evaluate {
+            return Err(GameError::IllegalMove);
+        }
+
+        // check if backhand wins
+        let (Some(lead), Some(follow), Some(forehand)) =
+            (&self.trick.0, &self.trick.1, self.forehand)
+        else {
+            return Err(GameError::IllegalMove);
+        };
+        let backhand_wins = wins(follow, lead, self.trump_suit);
 
-        let forehand = self.forehand.expect("There should always be a forehand, since this should only be called after a card was played");
         let winner = if backhand_wins {
             get_opponent(forehand)
         } else {
@@ -257,28 +806,25 @@ impl Game {
         };
 
         info!("{} won this trick", winner);
-        toasts.info(format!("{} won this trick", winner));
 
-        self.give_trick_to(winner, toasts);
+        self.give_trick_to(winner);
 
         if self.can_draw_card() {
-            self.deal_card(winner);
-            self.deal_card(get_opponent(winner));
+            self.deal_card(winner)?;
+            self.deal_card(get_opponent(winner))?;
         }
 
-        if self.end_game(toasts) {
-            return;
+        if self.end_game(winner) {
+            return Ok(());
         }
 
         // winner can play again
-        match winner {
-            EPlayer::PC => self.set_state(EGameState::PlayerTurn, time),
-            EPlayer::NPC => self.set_state(EGameState::NpcTurn, time),
-        }
+        self.state = get_turn_state(winner);
+        Ok(())
     }
 
-    /// .
-    fn give_trick_to(&mut self, player: EPlayer, _toasts: &mut Toasts) {
+    /// Moves the cards of the trick to the stack of the player
+    fn give_trick_to(&mut self, player: EPlayer) {
         if let Some(t1) = self.trick.0.take() {
             if let Some(t2) = self.trick.1.take() {
                 match player {
@@ -292,85 +838,293 @@ impl Game {
                     }
                 }
 
-                debug!("{} has {} points", player, self.get_points(player));
-                //toasts.info(format!("{} has {} points", player, self.get_points(player)));
+                let points = self.get_points(player);
+                debug!("{} has {} points", player, points);
+                self.emit(GameEvent::TrickWon { player, points });
             }
         }
     }
 
-    /// .
+    /// Cards are drawn as long as the talon is neither exhausted nor closed
     fn can_draw_card(&self) -> bool {
-        self.trump_card.is_some()
+        self.trump_card.is_some() && self.closed_by.is_none()
     }
 
-    /// .
-    // fn must_follow_suit(&self) -> bool {
-    //     self.trump_card.is_none()
-    // }
+    /// Checks if the strict rules apply, see [`must_follow_suit`]
+    pub fn must_follow_suit(&self) -> bool {
+        must_follow_suit(self.trump_card.as_ref(), self.closed_by)
+    }
 
-    /// Let the AI player choose a card
-    ///
-    /// # Panics
-    ///
-    /// Panics if .
-    fn ai_choose_card(&mut self) -> Card {
-        // todo ai strategy
-        self.npc_hand.pop().unwrap()
+    /// Checks if the player may close the talon:
+    /// the player must be on lead and at least three cards must remain in the talon
+    pub fn can_close_talon(&self, _player: EPlayer) -> bool {
+        self.trick.0.is_none()
+            && self.marriage_lead.is_none()
+            && self.closed_by.is_none()
+            && self.winner.is_none()
+            && self.trump_card.is_some()
+            && self.talon.len() >= 3
     }
 
-    /// Checks if the game should end
-    fn end_game(&mut self, toasts: &mut Toasts) -> bool {
+    /// Closes the talon by turning down the trump card. No more cards are drawn.
+    fn close_talon(&mut self, player: EPlayer) -> Result<(), GameError> {
+        self.check_turn(player)?;
+        if !self.can_close_talon(player) {
+            warn!("{} can't close the talon", player);
+            return Err(GameError::IllegalMove);
+        }
+
+        self.closed_by = Some(player);
+        self.closed_points = self.get_points(get_opponent(player));
+
+        debug!(
+            "{} closed the talon, the opponent has {} points",
+            player, self.closed_points
+        );
+        self.emit(GameEvent::TalonClosed { player });
+        Ok(())
+    }
+
+    /// Checks if the player may exchange the trump Unter for the face-up trump card:
+    /// the player must be on lead and the talon must still be open
+    pub fn can_exchange_trump(&self, player: EPlayer) -> bool {
+        if self.trick.0.is_some()
+            || self.marriage_lead.is_some()
+            || self.closed_by.is_some()
+            || self.winner.is_some()
+            || self.talon.is_empty()
+        {
+            return false;
+        }
+        let (Some(trump_suit), Some(trump_card)) = (self.trump_suit, &self.trump_card) else {
+            return false;
+        };
+        if trump_card.value == EValue::Unter {
+            return false;
+        }
+
+        let (hand, stack) = match player {
+            EPlayer::PC => (&self.player_hand, &self.player_stack),
+            EPlayer::NPC => (&self.npc_hand, &self.npc_stack),
+        };
+        if self.variant.exchange_needs_trick && stack.is_empty() {
+            return false;
+        }
+        hand.contains(&Card::new(trump_suit, EValue::Unter))
+    }
+
+    /// Exchanges the trump Unter in the hand of the player for the face-up trump card.
+    fn exchange_trump(&mut self, player: EPlayer) -> Result<(), GameError> {
+        self.check_turn(player)?;
+        let Some(trump_suit) = self.trump_suit else {
+            return Err(GameError::IllegalMove);
+        };
+
+        let unter = Card::new(trump_suit, EValue::Unter);
+        let hand = match player {
+            EPlayer::PC => &self.player_hand,
+            EPlayer::NPC => &self.npc_hand,
+        };
+        let Some(index) = hand.iter().position(|c| *c == unter) else {
+            return Err(GameError::CardNotInHand);
+        };
+        if !self.can_exchange_trump(player) {
+            warn!("{} can't exchange the trump Unter", player);
+            return Err(GameError::IllegalMove);
+        }
+
+        let hand = match player {
+            EPlayer::PC => &mut self.player_hand,
+            EPlayer::NPC => &mut self.npc_hand,
+        };
+        hand.remove(index);
+        if let Some(trump_card) = self.trump_card.replace(unter) {
+            debug!("{} exchanged the trump Unter for {}", player, trump_card);
+            hand.push(trump_card.clone());
+            self.shown.push((player, trump_card.clone()));
+            self.emit(GameEvent::TrumpExchanged {
+                player,
+                card: trump_card,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks if the game should end. When nobody claimed 66 the winner of the last trick wins.
+    fn end_game(&mut self, last_trick: EPlayer) -> bool {
         if self.player_hand.is_empty() && self.npc_hand.is_empty() {
             info!("The game ended.");
-            toasts.info("The game ended.");
 
-            // count cards in stacks
-            let player_count = self.get_points(EPlayer::PC);
-            let npc_count = self.get_points(EPlayer::NPC);
+            // the closing player must reach 66 or the opponent wins
+            if let Some(closer) = self.closed_by {
+                if self.get_points(closer) >= 66 {
+                    self.finish(closer, get_game_points(self.closed_points));
+                } else {
+                    // the opponent gets 3 points if they had no trick when the talon was closed
+                    let game_points = if self.closed_points == 0 { 3 } else { 2 };
+                    self.finish(get_opponent(closer), game_points);
+                }
 
-            // determine winner
-            if player_count > 66 {
-                self.winner = Some(EPlayer::PC);
-            } else if npc_count > 66 {
-                self.winner = Some(EPlayer::NPC);
+                return true;
             }
 
+            let loser_points = self.get_points(get_opponent(last_trick));
+            self.finish(last_trick, get_game_points(loser_points));
+
             return true;
         }
         false
     }
 
+    /// Checks if the player may claim to have 66 points, which is possible when on lead
+    pub fn can_claim_out(&self, _player: EPlayer) -> bool {
+        self.trick.0.is_none() && self.winner.is_none()
+    }
+
+    /// The player claims to have reached 66 points, which ends the game immediately.
+    ///
+    /// The claim is verified: the claiming player wins 1, 2 or 3 game points
+    /// depending on the points of the opponent. A false claim awards 2 game points to the opponent,
+    /// or 3 if the opponent has not taken a trick yet.
+    fn claim_out(&mut self, player: EPlayer) -> Result<GameResult, GameError> {
+        self.check_turn(player)?;
+        if !self.can_claim_out(player) {
+            warn!("{} can't claim 66 now", player);
+            return Err(GameError::IllegalMove);
+        }
+
+        let opponent = get_opponent(player);
+        self.emit(GameEvent::OutClaimed {
+            player,
+            points: self.get_points(player),
+        });
+        let result = if self.get_points(player) >= 66 {
+            let game_points = match self.closed_by {
+                // the opponent closed the talon and failed
+                Some(closer) if closer == opponent => {
+                    if self.closed_points == 0 {
+                        3
+                    } else {
+                        2
+                    }
+                }
+                Some(_) => get_game_points(self.closed_points),
+                None => get_game_points(self.get_points(opponent)),
+            };
+            self.finish(player, game_points)
+        } else {
+            warn!(
+                "{} claimed 66 with {} points",
+                player,
+                self.get_points(player)
+            );
+            let opponent_points = if self.closed_by == Some(player) {
+                self.closed_points
+            } else {
+                self.get_points(opponent)
+            };
+            let game_points = if opponent_points == 0 { 3 } else { 2 };
+            self.finish(opponent, game_points)
+        };
+
+        Ok(result)
+    }
+
+    /// Ends the game and awards the game points
+    fn finish(&mut self, player: EPlayer, game_points: usize) -> GameResult {
+        let result = GameResult {
+            player,
+            game_points,
+        };
+        info!("{}", result);
+
+        self.winner = Some(result);
+        self.marriage_lead = None;
+        self.state = EGameState::Finished;
+        self.emit(GameEvent::GameOver(result));
+        result
+    }
+
+    /// Gets the points of the player: cards in the stack and the declared marriages,
+    /// which only count once the player has taken a trick
     pub fn get_points(&self, player: EPlayer) -> usize {
-        match player {
-            EPlayer::PC => self.player_stack.iter().map(|c| c.value as usize).sum(),
-            EPlayer::NPC => self.npc_stack.iter().map(|c| c.value as usize).sum(),
+        let stack = match player {
+            EPlayer::PC => &self.player_stack,
+            EPlayer::NPC => &self.npc_stack,
+        };
+        if stack.is_empty() {
+            return 0;
         }
+
+        let cards: usize = stack.iter().map(|c| c.value as usize).sum();
+        let marriages: usize = self
+            .marriages
+            .iter()
+            .filter(|(p, _)| *p == player)
+            .map(|(_, s)| self.get_marriage_value(*s))
+            .sum();
+        cards + marriages
     }
+}
 
-    pub fn set_state(&mut self, state: EGameState, time: f64) {
-        self.state = Some(state);
-        self.last_turn_time = time;
+/// Checks if the card beats the card that was led: a higher card of the same suit or a trump
+pub fn wins(card: &Card, lead: &Card, trump_suit: Option<ESuit>) -> bool {
+    if card.suit == lead.suit {
+        card.value > lead.value
+    } else {
+        Some(card.suit) == trump_suit
     }
 }
 
-fn wins(card: &Card, played_card: &Card, trump: ESuit) -> bool {
-    if card.suit == played_card.suit {
-        card.value > played_card.value
+/// A marriage is worth 40 in the trump suit and 20 otherwise
+pub fn get_marriage_value(suit: ESuit, trump_suit: Option<ESuit>) -> usize {
+    if trump_suit == Some(suit) {
+        40
     } else {
-        card.suit == trump
+        20
     }
 }
 
+/// Strict rules apply once the talon is exhausted or closed:
+/// the trump card was drawn or turned down
+pub fn must_follow_suit(trump_card: Option<&Card>, closed_by: Option<EPlayer>) -> bool {
+    trump_card.is_none() || closed_by.is_some()
+}
+
+/// Checks if the card is the King or Ober of the suit
+pub fn is_marriage_card(card: &Card, suit: ESuit) -> bool {
+    card.suit == suit && (card.value == EValue::King || card.value == EValue::Ober)
+}
+
 // helper methods
 
-/// .
-pub fn get_deck_shuffled() -> Vec<Card> {
-    let mut rng = thread_rng();
+/// Game points of the winner depending on the points of the loser:
+/// 3 if the loser took no trick, 2 if the loser has less than 33 points and 1 otherwise
+pub fn get_game_points(loser_points: usize) -> usize {
+    if loser_points == 0 {
+        3
+    } else if loser_points < 33 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Gets a deck shuffled by the random number generator
+pub fn get_deck_shuffled<R: Rng + ?Sized>(rng: &mut R) -> Vec<Card> {
     let mut r = get_deck();
-    r.shuffle(&mut rng);
+    r.shuffle(rng);
     r
 }
 
+/// Derives a new seed from a seed and an index (splitmix64)
+pub fn mix_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
 /// .
 pub fn get_deck() -> Vec<Card> {
     let r = vec![
@@ -398,9 +1152,439 @@ pub fn get_deck() -> Vec<Card> {
     r
 }
 
+fn get_turn_state(player: EPlayer) -> EGameState {
+    match player {
+        EPlayer::PC => EGameState::PlayerTurn,
+        EPlayer::NPC => EGameState::NpcTurn,
+    }
+}
+
 fn get_opponent(player: EPlayer) -> EPlayer {
     match player {
         EPlayer::PC => EPlayer::NPC,
         EPlayer::NPC => EPlayer::PC,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED: u64 = 42;
+
+    pub(crate) fn card(suit: ESuit, value: EValue) -> Card {
+        Card::new(suit, value)
+    }
+
+    /// Deals a game with a fixed seed and puts the cards where the test needs them.
+    /// Hearts are trump, the talon is exhausted and the PC is on lead.
+    pub(crate) fn get_endgame(pc_hand: Vec<Card>, npc_hand: Vec<Card>) -> Game {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+        game.trump_suit = Some(ESuit::Hearts);
+        game.trump_card = None;
+        game.talon.clear();
+        game.player_hand = pc_hand;
+        game.npc_hand = npc_hand;
+        game.player_stack.clear();
+        game.npc_stack.clear();
+        game.state = EGameState::PlayerTurn;
+        game
+    }
+
+    /// Opens the talon again: the trump Ace is face up and three cards remain
+    fn open_talon(game: &mut Game) {
+        game.trump_card = Some(card(ESuit::Hearts, EValue::Ace));
+        game.talon = vec![
+            card(ESuit::Hearts, EValue::X),
+            card(ESuit::Hearts, EValue::King),
+            card(ESuit::Hearts, EValue::Ober),
+        ];
+    }
+
+    #[test]
+    fn follow_suit_and_head_the_trick() {
+        let mut game = get_endgame(
+            vec![card(ESuit::Bells, EValue::King)],
+            vec![
+                card(ESuit::Bells, EValue::Unter),
+                card(ESuit::Bells, EValue::Ace),
+                card(ESuit::Hearts, EValue::Unter),
+            ],
+        );
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+
+        assert_eq!(
+            game.legal_moves(EPlayer::NPC),
+            vec![card(ESuit::Bells, EValue::Ace)]
+        );
+        assert_eq!(
+            game.apply(Action::Play(card(ESuit::Bells, EValue::Unter))),
+            Err(GameError::IllegalMove)
+        );
+        assert_eq!(
+            game.apply(Action::Play(card(ESuit::Hearts, EValue::Unter))),
+            Err(GameError::IllegalMove)
+        );
+        assert!(game
+            .apply(Action::Play(card(ESuit::Bells, EValue::Ace)))
+            .is_ok());
+    }
+
+    #[test]
+    fn follow_suit_with_a_lower_card() {
+        let mut game = get_endgame(
+            vec![card(ESuit::Bells, EValue::King)],
+            vec![
+                card(ESuit::Bells, EValue::Unter),
+                card(ESuit::Bells, EValue::Ober),
+                card(ESuit::Hearts, EValue::Ace),
+            ],
+        );
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+
+        assert_eq!(
+            game.legal_moves(EPlayer::NPC),
+            vec![
+                card(ESuit::Bells, EValue::Unter),
+                card(ESuit::Bells, EValue::Ober)
+            ]
+        );
+    }
+
+    #[test]
+    fn trump_when_void() {
+        let mut game = get_endgame(
+            vec![card(ESuit::Bells, EValue::King)],
+            vec![
+                card(ESuit::Acorns, EValue::Ace),
+                card(ESuit::Hearts, EValue::Unter),
+            ],
+        );
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+
+        assert_eq!(
+            game.legal_moves(EPlayer::NPC),
+            vec![card(ESuit::Hearts, EValue::Unter)]
+        );
+        assert_eq!(
+            game.apply(Action::Play(card(ESuit::Acorns, EValue::Ace))),
+            Err(GameError::IllegalMove)
+        );
+    }
+
+    #[test]
+    fn any_card_when_void_without_trumps() {
+        let npc_hand = vec![
+            card(ESuit::Acorns, EValue::Ace),
+            card(ESuit::Leaves, EValue::Unter),
+        ];
+        let mut game = get_endgame(vec![card(ESuit::Bells, EValue::King)], npc_hand.clone());
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+
+        assert_eq!(game.legal_moves(EPlayer::NPC), npc_hand);
+    }
+
+    #[test]
+    fn any_card_while_the_talon_is_open() {
+        let npc_hand = vec![
+            card(ESuit::Bells, EValue::Unter),
+            card(ESuit::Acorns, EValue::Ace),
+        ];
+        let mut game = get_endgame(vec![card(ESuit::Bells, EValue::King)], npc_hand.clone());
+        open_talon(&mut game);
+        assert!(!game.must_follow_suit());
+
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+        assert_eq!(game.legal_moves(EPlayer::NPC), npc_hand);
+    }
+
+    #[test]
+    fn closing_the_talon_enforces_the_strict_rules() {
+        let mut game = get_endgame(
+            vec![card(ESuit::Bells, EValue::King)],
+            vec![
+                card(ESuit::Bells, EValue::Unter),
+                card(ESuit::Acorns, EValue::Ace),
+            ],
+        );
+        open_talon(&mut game);
+        game.apply(Action::Close).unwrap();
+        assert!(game.must_follow_suit());
+
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+        assert_eq!(
+            game.legal_moves(EPlayer::NPC),
+            vec![card(ESuit::Bells, EValue::Unter)]
+        );
+    }
+
+    /// 66 points in cards
+    fn get_sixty_six() -> Vec<Card> {
+        vec![
+            card(ESuit::Hearts, EValue::Ace),
+            card(ESuit::Bells, EValue::Ace),
+            card(ESuit::Acorns, EValue::Ace),
+            card(ESuit::Leaves, EValue::Ace),
+            card(ESuit::Hearts, EValue::X),
+            card(ESuit::Bells, EValue::X),
+            card(ESuit::Hearts, EValue::Unter),
+        ]
+    }
+
+    /// 34 points in cards
+    fn get_thirty_four() -> Vec<Card> {
+        vec![
+            card(ESuit::Acorns, EValue::X),
+            card(ESuit::Leaves, EValue::X),
+            card(ESuit::Acorns, EValue::King),
+            card(ESuit::Leaves, EValue::King),
+            card(ESuit::Acorns, EValue::Ober),
+            card(ESuit::Leaves, EValue::Ober),
+        ]
+    }
+
+    /// 20 points in cards
+    fn get_twenty() -> Vec<Card> {
+        vec![
+            card(ESuit::Acorns, EValue::X),
+            card(ESuit::Leaves, EValue::X),
+        ]
+    }
+
+    /// The PC is on lead with the stacks given, one card is left in each hand
+    fn get_claim(pc_stack: Vec<Card>, npc_stack: Vec<Card>) -> Game {
+        let mut game = get_endgame(
+            vec![card(ESuit::Bells, EValue::King)],
+            vec![card(ESuit::Bells, EValue::Ober)],
+        );
+        game.player_stack = pc_stack;
+        game.npc_stack = npc_stack;
+        game
+    }
+
+    fn claim_out(mut game: Game) -> GameResult {
+        game.apply(Action::ClaimOut).unwrap();
+        game.winner().unwrap()
+    }
+
+    /// Plays out the last trick, the PC takes it
+    fn play_last_trick(mut game: Game) -> GameResult {
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+        game.apply(Action::Play(card(ESuit::Bells, EValue::Ober)))
+            .unwrap();
+        game.apply(Action::Continue).unwrap();
+        game.winner().unwrap()
+    }
+
+    fn get_result(player: EPlayer, game_points: usize) -> GameResult {
+        GameResult {
+            player,
+            game_points,
+        }
+    }
+
+    #[test]
+    fn claim_scores_by_the_opponent_points() {
+        let game = get_claim(get_sixty_six(), get_thirty_four());
+        assert_eq!(game.get_points(EPlayer::PC), 66);
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 1));
+
+        let game = get_claim(get_sixty_six(), get_twenty());
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 2));
+
+        let game = get_claim(get_sixty_six(), vec![]);
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 3));
+    }
+
+    #[test]
+    fn false_claim_scores_for_the_opponent() {
+        let mut pc_stack = get_sixty_six();
+        pc_stack.pop();
+        let game = get_claim(pc_stack.clone(), get_twenty());
+        assert_eq!(game.get_points(EPlayer::PC), 64);
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 2));
+
+        let game = get_claim(pc_stack.clone(), get_thirty_four());
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 2));
+
+        let game = get_claim(pc_stack, vec![]);
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 3));
+    }
+
+    #[test]
+    fn claim_only_on_lead() {
+        let mut game = get_claim(get_sixty_six(), vec![]);
+        game.apply(Action::Play(card(ESuit::Bells, EValue::King)))
+            .unwrap();
+        assert!(!game.can_claim_out(EPlayer::NPC));
+        assert_eq!(game.apply(Action::ClaimOut), Err(GameError::IllegalMove));
+    }
+
+    #[test]
+    fn claim_after_closing_scores_by_the_points_at_closing() {
+        // the opponent had no trick when the talon was closed
+        let mut game = get_claim(get_sixty_six(), get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 0;
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 3));
+
+        let mut game = get_claim(get_sixty_six(), get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 20;
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 2));
+
+        let mut game = get_claim(get_sixty_six(), vec![]);
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 34;
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 1));
+    }
+
+    #[test]
+    fn claim_against_the_closer() {
+        let mut game = get_claim(get_sixty_six(), get_thirty_four());
+        game.closed_by = Some(EPlayer::NPC);
+        game.closed_points = 0;
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 3));
+
+        // the points of the closer don't matter
+        let mut game = get_claim(get_sixty_six(), get_thirty_four());
+        game.closed_by = Some(EPlayer::NPC);
+        game.closed_points = 20;
+        assert_eq!(claim_out(game), get_result(EPlayer::PC, 2));
+    }
+
+    #[test]
+    fn false_claim_of_the_closer() {
+        let mut game = get_claim(get_twenty(), get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 0;
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 3));
+
+        let mut game = get_claim(get_twenty(), vec![]);
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 20;
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 2));
+
+        // the opponent of the closer claims falsely, the closer has taken tricks
+        let mut game = get_claim(get_twenty(), get_thirty_four());
+        game.closed_by = Some(EPlayer::NPC);
+        game.closed_points = 0;
+        assert_eq!(claim_out(game), get_result(EPlayer::NPC, 2));
+    }
+
+    #[test]
+    fn last_trick_wins_without_a_close() {
+        let game = get_claim(get_twenty(), get_thirty_four());
+        assert_eq!(play_last_trick(game), get_result(EPlayer::PC, 1));
+
+        let game = get_claim(get_twenty(), get_twenty());
+        assert_eq!(play_last_trick(game), get_result(EPlayer::PC, 2));
+
+        let game = get_claim(vec![], vec![]);
+        assert_eq!(play_last_trick(game), get_result(EPlayer::PC, 3));
+    }
+
+    #[test]
+    fn closer_reaching_sixty_six_scores_by_the_points_at_closing() {
+        let mut pc_stack = get_sixty_six();
+        pc_stack.pop();
+        let mut game = get_claim(pc_stack, get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 20;
+        assert_eq!(play_last_trick(game), get_result(EPlayer::PC, 2));
+    }
+
+    #[test]
+    fn closer_falling_short_loses() {
+        let mut game = get_claim(get_twenty(), get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 20;
+        assert_eq!(play_last_trick(game), get_result(EPlayer::NPC, 2));
+
+        let mut game = get_claim(get_twenty(), get_thirty_four());
+        game.closed_by = Some(EPlayer::PC);
+        game.closed_points = 0;
+        assert_eq!(play_last_trick(game), get_result(EPlayer::NPC, 3));
+    }
+
+    /// Lets the seats act until it is the turn of the PC or the game is over
+    fn play_until_pc(game: &mut Game, seats: &mut Seats) {
+        loop {
+            for event in game.drain_events() {
+                seats.observe(&event);
+            }
+            if game.winner().is_some() || game.is_turn_of(EPlayer::PC) {
+                return;
+            }
+            assert!(seats.step(game).unwrap());
+        }
+    }
+
+    #[test]
+    fn undo_reproduces_the_earlier_games() {
+        for seed in 0..500 {
+            let mut game = Game::with_seed(seed);
+            game.play_with_dealer(EPlayer::NPC).unwrap();
+            let mut seats = Seats::new(
+                Some(Box::new(RandomStrategy::new(seed))),
+                Some(Box::new(HeuristicStrategy)),
+            );
+
+            // the positions before each decision of the PC
+            let mut positions = vec![];
+            while game.winner().is_none() {
+                if game.is_turn_of(EPlayer::PC) {
+                    positions.push((game.actions().to_vec(), game.player_hand.clone()));
+                }
+                seats.step(&mut game).unwrap();
+            }
+
+            // the action that ended the game is taken back too
+            while let Some((actions, hand)) = positions.pop() {
+                game.undo(EPlayer::PC).unwrap();
+                assert_eq!(game.actions(), actions, "seed {}", seed);
+                assert_eq!(game.player_hand, hand, "seed {}", seed);
+                assert!(game.winner().is_none());
+            }
+            assert_eq!(game.undo(EPlayer::PC), Err(GameError::NothingToUndo));
+        }
+    }
+
+    #[test]
+    fn undo_gives_the_same_reply() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::PC).unwrap();
+        let mut seats = Seats::new(
+            Some(Box::new(RandomStrategy::new(SEED))),
+            Some(Box::new(MonteCarloStrategy::new(50, SEED))),
+        );
+        // forgetting is reproduced too
+        seats.npc_tracker = CardTracker::new(EPlayer::NPC, 0.5, SEED);
+
+        play_until_pc(&mut game, &mut seats);
+        while game.winner().is_none() {
+            let before = game.actions().to_vec();
+            seats.step(&mut game).unwrap();
+            play_until_pc(&mut game, &mut seats);
+            let after = game.actions().to_vec();
+
+            game.undo(EPlayer::PC).unwrap();
+            for event in game.drain_events() {
+                seats.observe(&event);
+            }
+            assert_eq!(game.actions(), before);
+
+            seats.step(&mut game).unwrap();
+            play_until_pc(&mut game, &mut seats);
+            assert_eq!(game.actions(), after);
+        }
+    }
+}
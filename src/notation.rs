@@ -0,0 +1,511 @@
+use std::fmt::Display;
+
+use crate::{get_deck, Action, Card, EGameState, EPlayer, ESuit, Game, GameError, Variant};
+
+/// The rule of [`crate::Variant::exchange_needs_trick`] in the `Variant` header
+const EXCHANGE_NEEDS_TRICK: &str = "exchange-needs-trick";
+
+/// An error in a game in text notation with the position it was found at
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotationError {
+    /// the line, starting at 1
+    pub line: usize,
+    /// the column in characters, starting at 1
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+impl NotationError {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl Game {
+    /// Writes the game in a compact text notation to share it. A header of tags like `[Seed "42"]`
+    /// is followed by the moves, one line per trick. Cards are written like `Hearts.Ace`,
+    /// the other actions are `Marriage.Hearts`, `Exchange`, `Close` and `Claim`.
+    /// Evaluating a trick is implied by the next move or the number of the next trick.
+    ///
+    /// ```
+    /// use flin::{EPlayer, Game, HeuristicStrategy, Seats};
+    ///
+    /// let mut game = Game::with_seed(7);
+    /// game.play_with_dealer(EPlayer::NPC).unwrap();
+    /// let mut seats = Seats::new(Some(Box::new(HeuristicStrategy)), Some(Box::new(HeuristicStrategy)));
+    /// seats.play(&mut game).unwrap();
+    ///
+    /// let text = r#"[Seed "7"]
+    /// [Dealer "NPC"]
+    /// [Trump "Bells"]
+    /// [Result "PC 2"]
+    ///
+    /// 1. Exchange Hearts.Unter Hearts.Ober
+    /// 2. Hearts.King Hearts.X
+    /// 3. Acorns.Unter Acorns.King
+    /// 4. Leaves.Unter Leaves.Ober
+    /// 5. Marriage.Bells Bells.Ober Bells.Ace
+    /// 6. Acorns.Ace Bells.Unter
+    /// 7. Claim
+    /// "#;
+    /// assert_eq!(game.to_notation(), text);
+    /// ```
+    pub fn to_notation(&self) -> String {
+        let mut text = format!("[Seed \"{}\"]\n", self.seed());
+        if let Some(dealer) = self.dealer() {
+            text.push_str(&format!("[Dealer \"{}\"]\n", dealer));
+        }
+        if let Some(suit) = self.trump_suit {
+            text.push_str(&format!("[Trump \"{}\"]\n", suit));
+        }
+        if self.variant.exchange_needs_trick {
+            text.push_str(&format!("[Variant \"{}\"]\n", EXCHANGE_NEEDS_TRICK));
+        }
+        if let Some(result) = self.winner() {
+            text.push_str(&format!(
+                "[Result \"{} {}\"]\n",
+                result.player, result.game_points
+            ));
+        }
+
+        let mut trick = 1;
+        let mut line = vec![];
+        for action in self.actions() {
+            match get_token(action) {
+                Some(token) => line.push(token),
+                None => {
+                    text.push_str(&get_line(trick, &line));
+                    line.clear();
+                    trick += 1;
+                }
+            }
+        }
+        // the running trick, after an evaluated trick its number alone tells so
+        if !line.is_empty() || (self.winner().is_none() && !self.actions().is_empty()) {
+            text.push_str(&get_line(trick, &line));
+        }
+        text.push('\n');
+        text
+    }
+
+    /// Reads a game in the notation of [`Game::to_notation`] and replays its moves.
+    /// Unknown tags are ignored, `;` starts a comment. The trick numbers are optional.
+    ///
+    /// ```
+    /// use flin::{EPlayer, Game, HeuristicStrategy, Seats};
+    ///
+    /// let mut game = Game::with_seed(7);
+    /// game.play_with_dealer(EPlayer::NPC).unwrap();
+    /// let mut seats = Seats::new(Some(Box::new(HeuristicStrategy)), Some(Box::new(HeuristicStrategy)));
+    /// seats.play(&mut game).unwrap();
+    ///
+    /// let text = game.to_notation();
+    /// let copy = Game::from_notation(&text).unwrap();
+    /// assert_eq!(copy.actions(), game.actions());
+    /// assert_eq!(copy.winner(), game.winner());
+    /// assert_eq!(copy.to_notation(), text);
+    /// ```
+    ///
+    /// Errors tell where they are:
+    ///
+    /// ```
+    /// use flin::Game;
+    ///
+    /// let error = Game::from_notation("[Seed \"7\"]\n[Dealer \"NPC\"]\n\n1. Hearts.Queen").unwrap_err();
+    /// assert_eq!((error.line, error.column), (4, 4));
+    /// assert_eq!(error.to_string(), "line 4, column 4: unknown move Hearts.Queen");
+    /// ```
+    pub fn from_notation(text: &str) -> Result<Game, NotationError> {
+        let mut game: Option<Game> = None;
+        let mut trump: Option<(usize, usize, String)> = None;
+        let mut result: Option<(usize, usize, String)> = None;
+        let mut dealer = None;
+        let mut seed = None;
+        let mut exchange_needs_trick = false;
+        let mut end = (1, 1);
+
+        for (i, line) in text.lines().enumerate() {
+            let line_number = i + 1;
+            let line = line.split(';').next().unwrap_or_default();
+
+            for (column, token) in get_tokens(line) {
+                end = (line_number, column);
+
+                // the header
+                if token.starts_with('[') {
+                    if game.is_some() {
+                        return Err(NotationError::new(
+                            line_number,
+                            column,
+                            "tags must come before the moves",
+                        ));
+                    }
+                    let (key, value) = parse_tag(line, line_number, column)?;
+                    match key {
+                        "Seed" => {
+                            seed = Some(value.parse::<u64>().map_err(|_| {
+                                NotationError::new(line_number, column, "the seed must be a number")
+                            })?)
+                        }
+                        "Dealer" => {
+                            dealer = Some(parse_player(value).ok_or_else(|| {
+                                NotationError::new(
+                                    line_number,
+                                    column,
+                                    format!("unknown player {}", value),
+                                )
+                            })?)
+                        }
+                        "Trump" => trump = Some((line_number, column, value.to_owned())),
+                        "Result" => result = Some((line_number, column, value.to_owned())),
+                        "Variant" => {
+                            for rule in value.split_whitespace() {
+                                if rule != EXCHANGE_NEEDS_TRICK {
+                                    return Err(NotationError::new(
+                                        line_number,
+                                        column,
+                                        format!("unknown rule {}", rule),
+                                    ));
+                                }
+                                exchange_needs_trick = true;
+                            }
+                        }
+                        _ => {}
+                    }
+                    // the tag takes the whole line
+                    break;
+                }
+
+                // the first move deals the game
+                let game = match &mut game {
+                    Some(game) => game,
+                    None => game.insert(deal(
+                        seed,
+                        dealer,
+                        exchange_needs_trick,
+                        line_number,
+                        column,
+                    )?),
+                };
+
+                // trick numbers
+                if let Some(number) = token.strip_suffix('.') {
+                    let trick = get_trick_number(game);
+                    if number.parse::<usize>().ok() != Some(trick) {
+                        return Err(NotationError::new(
+                            line_number,
+                            column,
+                            format!("expected trick {}, found {}", trick, token),
+                        ));
+                    }
+                    if game.phase() == EGameState::Evaluate {
+                        game.apply(Action::Continue)
+                            .map_err(|e| NotationError::new(line_number, column, e.to_string()))?;
+                    }
+                    continue;
+                }
+
+                let action = parse_action(token).ok_or_else(|| {
+                    NotationError::new(line_number, column, format!("unknown move {}", token))
+                })?;
+                let error = |e: GameError| {
+                    NotationError::new(line_number, column, format!("{}: {}", token, e))
+                };
+                if game.phase() == EGameState::Evaluate {
+                    game.apply(Action::Continue).map_err(error)?;
+                }
+                game.apply(action).map_err(error)?;
+            }
+        }
+
+        let mut game = match game {
+            Some(game) => game,
+            None => deal(seed, dealer, exchange_needs_trick, end.0, end.1)?,
+        };
+
+        // the last trick of a finished game is evaluated
+        if result.is_some() && game.phase() == EGameState::Evaluate {
+            game.apply(Action::Continue)
+                .map_err(|e| NotationError::new(end.0, end.1, e.to_string()))?;
+        }
+
+        if let Some((line, column, value)) = trump {
+            let suit = game.trump_suit.map(|s| s.to_string()).unwrap_or_default();
+            if value != suit {
+                return Err(NotationError::new(
+                    line,
+                    column,
+                    format!("the seed deals {} as trump, not {}", suit, value),
+                ));
+            }
+        }
+        if let Some((line, column, value)) = result {
+            let winner = game
+                .winner()
+                .map(|r| format!("{} {}", r.player, r.game_points))
+                .unwrap_or_else(|| "no result".to_owned());
+            if value != winner {
+                return Err(NotationError::new(
+                    line,
+                    column,
+                    format!("the moves lead to {}, not {}", winner, value),
+                ));
+            }
+        }
+        Ok(game)
+    }
+}
+
+/// Deals the game of the header
+fn deal(
+    seed: Option<u64>,
+    dealer: Option<EPlayer>,
+    exchange_needs_trick: bool,
+    line: usize,
+    column: usize,
+) -> Result<Game, NotationError> {
+    let seed = seed.ok_or_else(|| NotationError::new(line, column, "the Seed tag is missing"))?;
+    let dealer =
+        dealer.ok_or_else(|| NotationError::new(line, column, "the Dealer tag is missing"))?;
+
+    let variant = Variant {
+        exchange_needs_trick,
+    };
+    Game::replay(seed, dealer, variant, &[])
+        .map_err(|e| NotationError::new(line, column, e.to_string()))
+}
+
+/// Splits a line at whitespace, the columns count characters from 1
+fn get_tokens(line: &str) -> Vec<(usize, &str)> {
+    let mut tokens = vec![];
+    let mut start: Option<(usize, usize)> = None;
+    for (column, (index, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (true, Some((start_column, start_index))) => {
+                tokens.push((start_column + 1, &line[start_index..index]));
+                start = None;
+            }
+            (false, None) => start = Some((column, index)),
+            _ => {}
+        }
+    }
+    if let Some((start_column, start_index)) = start {
+        tokens.push((start_column + 1, &line[start_index..]));
+    }
+    tokens
+}
+
+/// Reads a tag like `[Seed "42"]`, the tag starts at the column
+fn parse_tag(line: &str, line_number: usize, column: usize) -> Result<(&str, &str), NotationError> {
+    let error = || NotationError::new(line_number, column, "a tag must look like [Key \"value\"]");
+    let tag = line
+        .trim()
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(error)?;
+    let (key, value) = tag.split_once(' ').ok_or_else(error)?;
+    let value = value
+        .trim()
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .ok_or_else(error)?;
+    Ok((key, value))
+}
+
+fn parse_player(text: &str) -> Option<EPlayer> {
+    [EPlayer::PC, EPlayer::NPC]
+        .into_iter()
+        .find(|p| p.to_string() == text)
+}
+
+fn parse_card(text: &str) -> Option<Card> {
+    get_deck().into_iter().find(|c| c.to_string() == text)
+}
+
+fn parse_suit(text: &str) -> Option<ESuit> {
+    get_deck()
+        .into_iter()
+        .map(|c| c.suit)
+        .find(|s| s.to_string() == text)
+}
+
+fn parse_action(token: &str) -> Option<Action> {
+    match token {
+        "Exchange" => Some(Action::ExchangeTrump),
+        "Close" => Some(Action::Close),
+        "Claim" => Some(Action::ClaimOut),
+        _ => match token.strip_prefix("Marriage.") {
+            Some(suit) => parse_suit(suit).map(Action::DeclareMarriage),
+            None => parse_card(token).map(Action::Play),
+        },
+    }
+}
+
+fn get_line(trick: usize, tokens: &[String]) -> String {
+    let mut line = format!("\n{}.", trick);
+    for token in tokens {
+        line.push(' ');
+        line.push_str(token);
+    }
+    line
+}
+
+/// Gets the notation of an action, evaluating a trick has none
+fn get_token(action: &Action) -> Option<String> {
+    match action {
+        Action::Play(card) => Some(card.to_string()),
+        Action::DeclareMarriage(suit) => Some(format!("Marriage.{}", suit)),
+        Action::ExchangeTrump => Some("Exchange".to_owned()),
+        Action::Close => Some("Close".to_owned()),
+        Action::ClaimOut => Some("Claim".to_owned()),
+        Action::Continue => None,
+    }
+}
+
+/// Gets the number of the trick the next move belongs to, starting at 1
+fn get_trick_number(game: &Game) -> usize {
+    let won = game.tricks().iter().filter(|t| t.winner.is_some()).count();
+    match game.phase() {
+        // the next move evaluates the complete trick first
+        EGameState::Evaluate => won + 2,
+        _ => won + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HeuristicStrategy, RandomStrategy, Seats};
+
+    const SEED: u64 = 7;
+
+    /// Plays a game with a random and a heuristic player
+    fn play(seed: u64, variant: Variant) -> Game {
+        let mut game = Game::replay(seed, EPlayer::NPC, variant, &[]).unwrap();
+        let mut seats = Seats::new(
+            Some(Box::new(RandomStrategy::new(seed))),
+            Some(Box::new(HeuristicStrategy)),
+        );
+        seats.play(&mut game).unwrap();
+        game
+    }
+
+    fn assert_round_trip(game: &Game) {
+        let text = game.to_notation();
+        let copy = Game::from_notation(&text).unwrap_or_else(|e| panic!("{}\n{}", e, text));
+        assert_eq!(copy.actions(), game.actions(), "{}", text);
+        assert_eq!(copy.winner(), game.winner());
+        assert_eq!(copy.to_notation(), text);
+    }
+
+    fn get_error(text: &str) -> (usize, usize, String) {
+        let error = Game::from_notation(text).unwrap_err();
+        (error.line, error.column, error.message)
+    }
+
+    #[test]
+    fn games_with_every_move_round_trip() {
+        let mut texts = String::new();
+        for seed in 0..100 {
+            let game = play(seed, Variant::default());
+            assert_round_trip(&game);
+            texts.push_str(&game.to_notation());
+        }
+        for token in ["Close", "Exchange", "Marriage.", "Claim"] {
+            assert!(texts.contains(token), "no game with {}", token);
+        }
+    }
+
+    #[test]
+    fn unfinished_games_round_trip() {
+        let mut game = Game::replay(SEED, EPlayer::NPC, Variant::default(), &[]).unwrap();
+        assert_eq!(
+            game.to_notation(),
+            "[Seed \"7\"]\n[Dealer \"NPC\"]\n[Trump \"Bells\"]\n\n"
+        );
+        assert_round_trip(&game);
+
+        // a complete trick that is not evaluated yet
+        for _ in 0..2 {
+            let card = game.view(game.current_player().unwrap()).legal_moves()[0].clone();
+            game.apply(Action::Play(card)).unwrap();
+        }
+        assert!(game.to_notation().ends_with("\n1. Bells.X Hearts.Ober\n"));
+        assert_round_trip(&game);
+
+        // the evaluated trick leaves the number of the next one
+        game.apply(Action::Continue).unwrap();
+        assert!(game.to_notation().ends_with("\n2.\n"));
+        assert_round_trip(&game);
+    }
+
+    #[test]
+    fn variant_header() {
+        let game = play(
+            SEED,
+            Variant {
+                exchange_needs_trick: true,
+            },
+        );
+        let text = game.to_notation();
+        assert!(text.contains("[Variant \"exchange-needs-trick\"]\n"));
+        assert_round_trip(&game);
+        assert!(
+            Game::from_notation(&text)
+                .unwrap()
+                .variant
+                .exchange_needs_trick
+        );
+
+        assert!(!play(SEED, Variant::default())
+            .to_notation()
+            .contains("[Variant"));
+    }
+
+    #[test]
+    fn errors_tell_the_line_and_column() {
+        // a bad card name
+        assert_eq!(
+            get_error("[Seed \"7\"]\n[Dealer \"NPC\"]\n\n1. Hearts.Unter  Hearts.Queen"),
+            (4, 18, "unknown move Hearts.Queen".to_owned())
+        );
+
+        // an unknown rule and a broken tag, unknown tags are ignored
+        assert_eq!(
+            get_error("[Seed \"7\"]\n[Event \"Club night\"]\n  [Variant \"no-marriages\"]"),
+            (3, 3, "unknown rule no-marriages".to_owned())
+        );
+        assert_eq!(
+            get_error("[Seed \"7\"]\n[Dealer NPC]"),
+            (2, 1, "a tag must look like [Key \"value\"]".to_owned())
+        );
+        assert_eq!(
+            get_error("[Seed \"7\"]\n[Dealer \"NPC\"]\n\n1. Hearts.Unter\n[Trump \"Bells\"]"),
+            (5, 1, "tags must come before the moves".to_owned())
+        );
+
+        // an illegal move: the player doesn't hold the card
+        assert_eq!(
+            get_error(
+                "[Seed \"7\"]\n[Dealer \"NPC\"]\n\n1. Hearts.Unter Hearts.King\n2. Leaves.Ace"
+            ),
+            (5, 4, "Leaves.Ace: The card is not in the hand".to_owned())
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{Action, EPlayer, Game, GameError, Variant};
+
+/// Steps through a recorded game. Every position is rebuilt with the rules engine:
+/// the cards are dealt again from the seed and the actions are applied up to the position.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    seed: u64,
+    dealer: EPlayer,
+    variant: Variant,
+    actions: Vec<Action>,
+    /// the players who took the actions, none for evaluating a trick
+    players: Vec<Option<EPlayer>>,
+    game: Game,
+}
+
+impl Replay {
+    /// Creates a [`Replay`] of a finished game, it starts at the deal.
+    /// Running games are refused, the replay reveals all hands and the talon.
+    pub fn new(game: &Game) -> Result<Self, GameError> {
+        if game.winner().is_none() {
+            return Err(GameError::NotFinished);
+        }
+        let dealer = game.dealer().ok_or(GameError::NotStarted)?;
+        let start = Game::replay(game.seed(), dealer, game.variant.clone(), &[])?;
+
+        // the actions are checked once, so going to a position can't fail later
+        let mut end = start.clone();
+        let mut players = vec![];
+        for action in game.actions() {
+            players.push(end.current_player());
+            end.apply(action.clone())?;
+        }
+
+        Ok(Self {
+            seed: game.seed(),
+            dealer,
+            variant: game.variant.clone(),
+            actions: game.actions().to_vec(),
+            players,
+            game: start,
+        })
+    }
+
+    /// Deals the cards of the recorded game again
+    fn deal(&self) -> Result<Game, GameError> {
+        Game::replay(self.seed, self.dealer, self.variant.clone(), &[])
+    }
+
+    /// The game at the current position
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// The number of actions applied so far
+    pub fn position(&self) -> usize {
+        self.game.actions().len()
+    }
+
+    /// The number of actions in the recorded game
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Goes to the position, going back deals the game again
+    pub fn go_to(&mut self, position: usize) -> Result<(), GameError> {
+        let position = position.min(self.len());
+        if position < self.position() {
+            self.game = self.deal()?;
+        }
+        for action in &self.actions[self.position()..position] {
+            self.game.apply(action.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Applies the next action
+    pub fn step_forward(&mut self) -> Result<(), GameError> {
+        self.go_to(self.position() + 1)
+    }
+
+    /// Takes back the last action
+    pub fn step_back(&mut self) -> Result<(), GameError> {
+        self.go_to(self.position().saturating_sub(1))
+    }
+
+    /// Gets the positions the tricks start at, the first one is the deal
+    pub fn get_trick_starts(&self) -> Vec<usize> {
+        let mut starts = vec![0];
+        for (i, action) in self.actions.iter().enumerate() {
+            if *action == Action::Continue && i + 1 < self.len() {
+                starts.push(i + 1);
+            }
+        }
+        starts
+    }
+
+    /// Gets the index of the trick at the current position
+    pub fn get_trick(&self) -> usize {
+        let position = self.position();
+        self.get_trick_starts()
+            .iter()
+            .filter(|start| **start <= position)
+            .count()
+            .saturating_sub(1)
+    }
+
+    /// Goes to the start of the trick
+    pub fn go_to_trick(&mut self, trick: usize) -> Result<(), GameError> {
+        let starts = self.get_trick_starts();
+        let position = starts.get(trick).or(starts.last()).copied().unwrap_or(0);
+        self.go_to(position)
+    }
+
+    /// Gets the last applied action and the player who took it
+    pub fn get_last_action(&self) -> Option<(Option<EPlayer>, &Action)> {
+        let i = self.position().checked_sub(1)?;
+        Some((self.players.get(i).copied().flatten(), &self.actions[i]))
+    }
+}
@@ -0,0 +1,300 @@
+use std::time::Duration;
+
+use instant::Instant;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    ai::get_decision_seed, heuristic_action, Action, CardTracker, Decision, EGameState, Game,
+    GameError, GameResult, PlayerView, Strategy,
+};
+
+/// The exploration constant of the UCB1 formula
+const EXPLORATION: f64 = 1.4;
+
+/// The number of sampled games per step of a [`Search`]
+const BATCH: usize = 20;
+
+/// A strategy that samples the unseen cards from the view, plays each sampled game to the end
+/// with the heuristic for both players and picks the action with the best expected game points
+/// (determinized Monte Carlo with UCB1 at the root)
+#[derive(Debug, Clone)]
+pub struct MonteCarloStrategy {
+    /// the number of sampled games per decision
+    pub iterations: usize,
+    /// stops the search early once the time is up
+    pub time_limit: Option<Duration>,
+    /// the seed of the sampling, the same seed and view give the same decision unless the time is limited
+    pub seed: u64,
+}
+
+impl Default for MonteCarloStrategy {
+    fn default() -> Self {
+        Self::new(1000, 0)
+    }
+}
+
+impl MonteCarloStrategy {
+    pub fn new(iterations: usize, seed: u64) -> Self {
+        Self {
+            iterations,
+            time_limit: None,
+            seed,
+        }
+    }
+
+    /// Limits the time of a decision. How many games are sampled then depends on the machine,
+    /// so the same seed and view may give different decisions.
+    pub fn with_time_limit(mut self, time_limit: Duration) -> Self {
+        self.time_limit = Some(time_limit);
+        self
+    }
+
+    /// Starts a search of the legal actions of the player.
+    /// The unseen cards are sampled from what the tracker remembers.
+    pub fn search(&self, view: &PlayerView, tracker: &CardTracker) -> Result<Search, GameError> {
+        if view.legal_actions.is_empty() {
+            return Err(GameError::NotYourTurn);
+        }
+
+        let actions = view.legal_actions.len();
+        Ok(Search {
+            view: view.clone(),
+            tracker: tracker.clone(),
+            rng: StdRng::seed_from_u64(get_decision_seed(self.seed, view)),
+            visits: vec![0; actions],
+            totals: vec![0.0; actions],
+            iteration: 0,
+            // every action is tried at least once
+            iterations: self.iterations.max(actions),
+            time_limit: self.time_limit,
+            start: Instant::now(),
+        })
+    }
+
+    /// Estimates the game points each legal action wins on average, negative values are losses.
+    /// The unseen cards are sampled from what the tracker remembers.
+    pub fn evaluate_actions(
+        &self,
+        view: &PlayerView,
+        tracker: &CardTracker,
+    ) -> Result<Vec<(Action, f64)>, GameError> {
+        let mut search = self.search(view, tracker)?;
+        while search.step()? {}
+        Ok(search.get_values())
+    }
+}
+
+impl Strategy for MonteCarloStrategy {
+    fn name(&self) -> String {
+        "Monte Carlo".to_owned()
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        tracker: &CardTracker,
+    ) -> Result<Action, GameError> {
+        self.start(view, tracker)?.run()
+    }
+
+    fn start(&mut self, view: &PlayerView, tracker: &CardTracker) -> Result<Decision, GameError> {
+        if let [action] = view.legal_actions.as_slice() {
+            return Ok(Decision::Done(action.clone()));
+        }
+        Ok(Decision::Searching(Box::new(self.search(view, tracker)?)))
+    }
+}
+
+/// A running search of a [`MonteCarloStrategy`]. Each call of [`Search::step`] samples a few games,
+/// so it can run a bit per frame or on a thread.
+#[derive(Debug, Clone)]
+pub struct Search {
+    view: PlayerView,
+    tracker: CardTracker,
+    rng: StdRng,
+    visits: Vec<usize>,
+    totals: Vec<f64>,
+    iteration: usize,
+    iterations: usize,
+    time_limit: Option<Duration>,
+    start: Instant,
+}
+
+impl Search {
+    /// Samples the next games. Returns false once the search is done.
+    pub fn step(&mut self) -> Result<bool, GameError> {
+        let actions = &self.view.legal_actions;
+        for _ in 0..BATCH {
+            if self.is_done() {
+                return Ok(false);
+            }
+
+            let index = select(&self.visits, &self.totals, self.iteration);
+            let mut game = self.tracker.determinize(&self.view, &mut self.rng);
+            game.apply(actions[index].clone())?;
+            let result = simulate(&mut game)?;
+            self.visits[index] += 1;
+            self.totals[index] += get_score(&self.view, result);
+            self.iteration += 1;
+        }
+        Ok(!self.is_done())
+    }
+
+    /// Checks if all games are sampled or the time is up. Every action is tried at least once.
+    pub fn is_done(&self) -> bool {
+        self.iteration >= self.iterations
+            || (self.iteration >= self.view.legal_actions.len()
+                && self.time_limit.is_some_and(|t| self.start.elapsed() > t))
+    }
+
+    /// Gets the game points each legal action won on average so far, negative values are losses
+    pub fn get_values(&self) -> Vec<(Action, f64)> {
+        self.view
+            .legal_actions
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.clone(), self.totals[i] / self.visits[i].max(1) as f64))
+            .collect()
+    }
+
+    /// Gets the action with the best average so far
+    pub fn get_best(&self) -> Option<Action> {
+        self.get_values()
+            .into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(action, _)| action)
+    }
+}
+
+/// Plays the game to the end with the heuristic for both players
+pub fn simulate(game: &mut Game) -> Result<GameResult, GameError> {
+    loop {
+        if let Some(result) = game.winner() {
+            return Ok(result);
+        }
+        let action = match (game.phase(), game.current_player()) {
+            (EGameState::Evaluate, _) => Action::Continue,
+            (_, Some(player)) => heuristic_action(&game.view(player))?,
+            (_, None) => return Err(GameError::NotStarted),
+        };
+        game.apply(action)?;
+    }
+}
+
+/// Picks the action to sample next with UCB1, untried actions first
+fn select(visits: &[usize], totals: &[f64], iteration: usize) -> usize {
+    if let Some(index) = visits.iter().position(|v| *v == 0) {
+        return index;
+    }
+
+    let log_n = (iteration as f64).ln();
+    let ucb = |i: usize| {
+        // game points range from -3 to 3
+        let mean = (totals[i] / visits[i] as f64 + 3.0) / 6.0;
+        mean + EXPLORATION * (log_n / visits[i] as f64).sqrt()
+    };
+    (0..visits.len())
+        .max_by(|a, b| ucb(*a).total_cmp(&ucb(*b)))
+        .unwrap_or_default()
+}
+
+/// The game points from the point of view of the player
+fn get_score(view: &PlayerView, result: GameResult) -> f64 {
+    if result.player == view.player {
+        result.game_points as f64
+    } else {
+        -(result.game_points as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Card, EPlayer, ESuit, EValue};
+
+    const SEED: u64 = 7;
+
+    fn get_tracker(game: &Game, player: EPlayer) -> CardTracker {
+        let mut tracker = CardTracker::new(player, 0.0, 0);
+        for event in game.history() {
+            tracker.observe(event);
+        }
+        tracker
+    }
+
+    fn get_action(strategy: &mut MonteCarloStrategy, game: &Game, player: EPlayer) -> Action {
+        strategy
+            .choose_action(&game.view(player), &get_tracker(game, player))
+            .unwrap()
+    }
+
+    #[test]
+    fn same_view_and_seed_give_the_same_action() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+
+        let mut strategy = MonteCarloStrategy::new(200, 11);
+        let lead = get_action(&mut strategy, &game, EPlayer::PC);
+        assert_eq!(get_action(&mut strategy, &game, EPlayer::PC), lead);
+
+        game.apply(lead).unwrap();
+        let mut strategy = MonteCarloStrategy::new(200, 11);
+        let answer = get_action(&mut strategy, &game, EPlayer::NPC);
+        assert_eq!(get_action(&mut strategy, &game, EPlayer::NPC), answer);
+    }
+
+    #[test]
+    fn view_and_seed_give_a_known_action() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+
+        let mut strategy = MonteCarloStrategy::new(200, 11);
+        let lead = get_action(&mut strategy, &game, EPlayer::PC);
+        assert_eq!(lead, Action::Play(Card::new(ESuit::Bells, EValue::X)));
+
+        game.apply(lead).unwrap();
+        assert_eq!(
+            get_action(&mut strategy, &game, EPlayer::NPC),
+            Action::Play(Card::new(ESuit::Bells, EValue::Ace))
+        );
+    }
+
+    #[test]
+    fn decision_in_steps_gives_the_same_action() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+        let view = game.view(EPlayer::PC);
+        let tracker = get_tracker(&game, EPlayer::PC);
+
+        let mut strategy = MonteCarloStrategy::new(200, 11);
+        let mut decision = strategy.start(&view, &tracker).unwrap();
+        let mut steps = 1;
+        let action = loop {
+            if let Some(action) = decision.step().unwrap() {
+                break action;
+            }
+            steps += 1;
+        };
+        assert_eq!(steps, 200 / BATCH);
+        assert_eq!(action, strategy.choose_action(&view, &tracker).unwrap());
+    }
+
+    #[test]
+    fn time_limit_is_honoured() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::NPC).unwrap();
+        let view = game.view(EPlayer::PC);
+        let tracker = get_tracker(&game, EPlayer::PC);
+
+        // without the limit the search would not end
+        let strategy =
+            MonteCarloStrategy::new(usize::MAX, 11).with_time_limit(Duration::from_millis(50));
+        let start = Instant::now();
+        let mut search = strategy.search(&view, &tracker).unwrap();
+        while search.step().unwrap() {}
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // every action is still tried
+        assert!(search.visits.iter().all(|v| *v > 0));
+    }
+}
@@ -0,0 +1,256 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::{
+    get_opponent, Action, CardTracker, Decision, EGameState, EPlayer, Game, GameError, GameResult,
+    PlayerView, Strategy,
+};
+
+/// The outcome of a game when both players play perfectly
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    /// the best action of the current player
+    pub action: Action,
+    pub result: GameResult,
+}
+
+/// Solves the game with an alpha-beta search over all remaining actions.
+/// The solver sees all cards, so it is meant for the endgame, when the players can deduce them,
+/// or for analysis. With at most 5 cards per hand it is instant.
+pub fn solve(game: &Game) -> Result<Solution, GameError> {
+    if game.winner().is_some() {
+        return Err(GameError::GameOver);
+    }
+    let player = game.current_player().ok_or(GameError::NotStarted)?;
+
+    let mut game = game.clone();
+    game.events.clear();
+    game.history.clear();
+
+    let mut best: Option<(Action, i32)> = None;
+    let mut alpha = i32::MIN;
+    for action in order_actions(game.legal_actions()) {
+        let mut next = game.clone();
+        next.apply(action.clone())?;
+        let value = search(&next, player, alpha, i32::MAX)?;
+        if best.as_ref().is_none_or(|(_, v)| value > *v) {
+            alpha = value;
+            best = Some((action, value));
+        }
+    }
+
+    let (action, value) = best.ok_or(GameError::GameOver)?;
+    let result = if value > 0 {
+        GameResult {
+            player,
+            game_points: value as usize,
+        }
+    } else {
+        GameResult {
+            player: get_opponent(player),
+            game_points: (-value) as usize,
+        }
+    };
+    Ok(Solution { action, result })
+}
+
+/// Gets the game points every legal action of the current player wins with best play from both sides,
+/// negative values are losses
+pub fn evaluate(game: &Game) -> Result<Vec<(Action, i32)>, GameError> {
+    let player = game.current_player().ok_or(GameError::NotYourTurn)?;
+
+    let mut game = game.clone();
+    game.events.clear();
+    game.history.clear();
+
+    let mut values = vec![];
+    for action in game.legal_actions() {
+        let mut next = game.clone();
+        next.apply(action.clone())?;
+        values.push((action, search(&next, player, i32::MIN, i32::MAX)?));
+    }
+    Ok(values)
+}
+
+/// The game points of the player with best play from here, negative if the player loses
+fn search(game: &Game, player: EPlayer, mut alpha: i32, mut beta: i32) -> Result<i32, GameError> {
+    if let Some(result) = game.winner() {
+        return Ok(get_value(result, player));
+    }
+    if game.phase() == EGameState::Evaluate {
+        let mut next = game.clone();
+        next.apply(Action::Continue)?;
+        return search(&next, player, alpha, beta);
+    }
+
+    let maximize = game.is_turn_of(player);
+    let mut best = if maximize { i32::MIN } else { i32::MAX };
+    for action in order_actions(game.legal_actions()) {
+        let mut next = game.clone();
+        next.apply(action)?;
+        let value = search(&next, player, alpha, beta)?;
+        if maximize {
+            best = best.max(value);
+            alpha = alpha.max(value);
+        } else {
+            best = best.min(value);
+            beta = beta.min(value);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+/// Tries the actions that end the game first, they cut off the search early
+fn order_actions(mut actions: Vec<Action>) -> Vec<Action> {
+    actions.sort_by_key(|a| match a {
+        Action::ClaimOut => 0,
+        Action::DeclareMarriage(_) => 1,
+        _ => 2,
+    });
+    actions
+}
+
+fn get_value(result: GameResult, player: EPlayer) -> i32 {
+    if result.player == player {
+        result.game_points as i32
+    } else {
+        -(result.game_points as i32)
+    }
+}
+
+/// Plays perfectly as soon as the player knows where all cards are,
+/// e.g. after the talon is exhausted, and leaves the rest of the game to another strategy
+pub struct EndgameStrategy {
+    pub fallback: Box<dyn Strategy>,
+}
+
+impl EndgameStrategy {
+    pub fn new(fallback: Box<dyn Strategy>) -> Self {
+        Self { fallback }
+    }
+}
+
+impl Strategy for EndgameStrategy {
+    fn name(&self) -> String {
+        format!("{} + Endgame", self.fallback.name())
+    }
+
+    fn choose_action(
+        &mut self,
+        view: &PlayerView,
+        tracker: &CardTracker,
+    ) -> Result<Action, GameError> {
+        if !tracker.knows_all_cards(view) {
+            return self.fallback.choose_action(view, tracker);
+        }
+
+        // the opponent holds all candidates, the sampled game is the real one
+        let game = tracker.determinize(view, &mut StdRng::seed_from_u64(0));
+        Ok(solve(&game)?.action)
+    }
+
+    fn start(&mut self, view: &PlayerView, tracker: &CardTracker) -> Result<Decision, GameError> {
+        if !tracker.knows_all_cards(view) {
+            return self.fallback.start(view, tracker);
+        }
+        Ok(Decision::Done(self.choose_action(view, tracker)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{card, get_endgame};
+    use crate::{ESuit, EValue};
+
+    /// The PC is on lead with 52 points and the trump Ace.
+    /// Taking the trick with it lets the PC claim 66 before the NPC made a point.
+    fn get_claim_line() -> Game {
+        let mut game = get_endgame(
+            vec![
+                card(ESuit::Hearts, EValue::Ace),
+                card(ESuit::Bells, EValue::Unter),
+            ],
+            vec![
+                card(ESuit::Hearts, EValue::X),
+                card(ESuit::Bells, EValue::Ace),
+            ],
+        );
+        game.player_stack = vec![
+            card(ESuit::Bells, EValue::X),
+            card(ESuit::Acorns, EValue::X),
+            card(ESuit::Leaves, EValue::X),
+            card(ESuit::Acorns, EValue::Ace),
+            card(ESuit::Leaves, EValue::Ace),
+        ];
+        game
+    }
+
+    #[test]
+    fn solve_finds_the_best_line() {
+        let mut game = get_claim_line();
+        let solution = solve(&game).unwrap();
+        assert_eq!(
+            solution.action,
+            Action::Play(card(ESuit::Hearts, EValue::Ace))
+        );
+        assert_eq!(
+            solution.result,
+            GameResult {
+                player: EPlayer::PC,
+                game_points: 3
+            }
+        );
+
+        // the PC claims right after taking the trick
+        game.apply(solution.action).unwrap();
+        game.apply(Action::Play(card(ESuit::Hearts, EValue::X)))
+            .unwrap();
+        game.apply(Action::Continue).unwrap();
+        assert_eq!(solve(&game).unwrap().action, Action::ClaimOut);
+    }
+
+    #[test]
+    fn evaluate_scores_every_action() {
+        let values = evaluate(&get_claim_line()).unwrap();
+        let get_value = |action: Action| values.iter().find(|(a, _)| *a == action).unwrap().1;
+
+        assert_eq!(values.len(), 3);
+        assert_eq!(get_value(Action::Play(card(ESuit::Hearts, EValue::Ace))), 3);
+        // the NPC takes the Bells with 13 points, the PC still takes the last trick
+        assert_eq!(
+            get_value(Action::Play(card(ESuit::Bells, EValue::Unter))),
+            2
+        );
+        // 52 points are not enough
+        assert_eq!(get_value(Action::ClaimOut), -3);
+    }
+
+    #[test]
+    fn solve_is_kept_when_both_play_it() {
+        for seed in 0..5 {
+            let mut game = Game::with_seed(seed);
+            game.play_with_dealer(EPlayer::NPC).unwrap();
+            game.apply(Action::Close).unwrap();
+
+            let expected = solve(&game).unwrap().result;
+            while game.winner().is_none() {
+                if game.phase() == EGameState::Evaluate {
+                    game.apply(Action::Continue).unwrap();
+                } else {
+                    game.apply(solve(&game).unwrap().action).unwrap();
+                }
+            }
+            assert_eq!(game.winner(), Some(expected), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn solve_refuses_finished_games() {
+        let mut game = get_claim_line();
+        game.apply(Action::ClaimOut).unwrap();
+        assert_eq!(solve(&game), Err(GameError::GameOver));
+    }
+}
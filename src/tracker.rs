@@ -0,0 +1,388 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{get_deck, mix_seed, Card, EPlayer, ESuit, EValue, Game, GameEvent, PlayerView};
+
+/// The number of cards a player gets until the talon is exhausted
+const CARDS_PER_GAME: usize = 10;
+
+/// Remembers the cards a player has seen during a game and what they tell about the hand of the opponent.
+/// It learns from the [`GameEvent`]s the player may see and can forget cards on purpose.
+#[derive(Debug, Clone)]
+pub struct CardTracker {
+    pub player: EPlayer,
+    /// the chance to forget a card that was played or shown, 0 remembers everything
+    pub forget_rate: f64,
+    /// the cards that were played into tricks
+    played: Vec<Card>,
+    /// the cards that are known to be in the hand of the opponent
+    opponent_cards: Vec<Card>,
+    /// the suits the opponent has no cards of
+    voids: Vec<ESuit>,
+    trump_suit: Option<ESuit>,
+    /// the face-up trump card under the talon
+    trump_card: Option<Card>,
+    /// the first card of the current trick
+    lead: Option<Card>,
+    /// the number of cards the player got in this game
+    dealt: usize,
+    /// strict rules apply once the talon is exhausted or closed
+    strict: bool,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl CardTracker {
+    pub fn new(player: EPlayer, forget_rate: f64, seed: u64) -> Self {
+        Self {
+            player,
+            forget_rate,
+            played: vec![],
+            opponent_cards: vec![],
+            voids: vec![],
+            trump_suit: None,
+            trump_card: None,
+            lead: None,
+            dealt: 0,
+            strict: false,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Learns from an event of the game. Events the player may not see are ignored.
+    pub fn observe(&mut self, event: &GameEvent) {
+        if !event.is_visible_to(self.player) {
+            return;
+        }
+
+        match event {
+            GameEvent::GameStarted { seed, .. } => {
+                // forgetting is reproduced when a game is replayed, but differs between games
+                let seed = mix_seed(self.seed, *seed);
+                *self = Self {
+                    seed: self.seed,
+                    ..Self::new(self.player, self.forget_rate, seed)
+                };
+            }
+            GameEvent::TrumpRevealed(card) => {
+                self.trump_suit = Some(card.suit);
+                self.trump_card = Some(card.clone());
+            }
+            GameEvent::CardDealt { card, .. } => {
+                self.dealt += 1;
+                if self.trump_card.as_ref() == Some(card) {
+                    self.trump_card = None;
+                }
+                if self.dealt == CARDS_PER_GAME {
+                    // the talon is exhausted, the opponent draws the trump card if the player didn't
+                    self.strict = true;
+                    if let Some(trump_card) = self.trump_card.take() {
+                        self.remember_opponent_card(trump_card);
+                    }
+                }
+            }
+            GameEvent::TrumpExchanged { player, card } => {
+                if let Some(suit) = self.trump_suit {
+                    self.trump_card = Some(Card::new(suit, EValue::Unter));
+                }
+                if *player != self.player {
+                    self.remember_opponent_card(card.clone());
+                }
+            }
+            GameEvent::MarriageDeclared { player, suit, .. } if *player != self.player => {
+                self.remember_opponent_card(Card::new(*suit, EValue::King));
+                self.remember_opponent_card(Card::new(*suit, EValue::Ober));
+            }
+            GameEvent::TalonClosed { .. } => {
+                self.strict = true;
+            }
+            GameEvent::CardPlayed { player, card } => {
+                self.opponent_cards.retain(|c| c != card);
+                if *player != self.player {
+                    self.infer_void(card);
+                }
+                self.lead = match self.lead {
+                    Some(_) => None,
+                    None => Some(card.clone()),
+                };
+                if !self.forgets() {
+                    self.played.push(card.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Under strict rules the opponent must follow suit and otherwise trump
+    fn infer_void(&mut self, card: &Card) {
+        let Some(lead) = &self.lead else {
+            return;
+        };
+        if !self.strict || card.suit == lead.suit {
+            return;
+        }
+
+        let mut voids = vec![lead.suit];
+        if Some(card.suit) != self.trump_suit {
+            voids.extend(self.trump_suit);
+        }
+        for suit in voids {
+            if !self.voids.contains(&suit) {
+                self.voids.push(suit);
+            }
+        }
+    }
+
+    fn remember_opponent_card(&mut self, card: Card) {
+        if !self.forgets() && !self.opponent_cards.contains(&card) {
+            self.opponent_cards.push(card);
+        }
+    }
+
+    fn forgets(&mut self) -> bool {
+        self.forget_rate > 0.0 && self.rng.gen_bool(self.forget_rate.min(1.0))
+    }
+
+    /// Gets the suits the opponent has no cards of
+    pub fn get_voids(&self) -> &[ESuit] {
+        &self.voids
+    }
+
+    /// Gets the cards the player remembers in the hand of the opponent
+    pub fn get_opponent_cards(&self, view: &PlayerView) -> Vec<Card> {
+        self.opponent_cards
+            .iter()
+            .filter(|c| !view.hand.contains(c) && !is_on_table(view, c))
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the cards the player can't place: they are in the talon or the hand of the opponent.
+    /// Forgotten cards are unknown again.
+    pub fn get_unknown(&self, view: &PlayerView) -> Vec<Card> {
+        let opponent_cards = self.get_opponent_cards(view);
+        get_deck()
+            .into_iter()
+            .filter(|c| {
+                !view.hand.contains(c)
+                    && !is_on_table(view, c)
+                    && !self.played.contains(c)
+                    && !opponent_cards.contains(c)
+            })
+            .collect()
+    }
+
+    /// Gets the cards the opponent may hold
+    pub fn get_opponent_candidates(&self, view: &PlayerView) -> Vec<Card> {
+        let mut cards = self.get_opponent_cards(view);
+        cards.extend(
+            self.get_unknown(view)
+                .into_iter()
+                .filter(|c| !self.voids.contains(&c.suit)),
+        );
+        cards
+    }
+
+    /// Gets the cards the opponent can't hold
+    pub fn get_impossible(&self, view: &PlayerView) -> Vec<Card> {
+        let candidates = self.get_opponent_candidates(view);
+        get_deck()
+            .into_iter()
+            .filter(|c| !candidates.contains(c))
+            .collect()
+    }
+
+    /// Checks if the player can place every card, e.g. after the talon is exhausted
+    pub fn knows_all_cards(&self, view: &PlayerView) -> bool {
+        self.get_opponent_candidates(view).len() == view.opponent_hand_len
+    }
+
+    /// Samples a [`Game`] that is consistent with what the player remembers
+    pub fn determinize<R: Rng + ?Sized>(&self, view: &PlayerView, rng: &mut R) -> Game {
+        view.deal(
+            self.get_opponent_cards(view),
+            self.get_unknown(view),
+            &self.voids,
+            rng,
+        )
+    }
+}
+
+/// Checks if the card lies open on the table: in the trick or as the trump card
+fn is_on_table(view: &PlayerView, card: &Card) -> bool {
+    view.trick.0.as_ref() == Some(card)
+        || view.trick.1.as_ref() == Some(card)
+        || view.trump_card.as_ref() == Some(card)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{card, get_endgame};
+
+    const SEED: u64 = 42;
+
+    fn play(player: EPlayer, suit: ESuit, value: EValue) -> GameEvent {
+        GameEvent::CardPlayed {
+            player,
+            card: card(suit, value),
+        }
+    }
+
+    /// Lets the tracker see the start of a game and the trump card
+    fn start(tracker: &mut CardTracker, trump_card: Card) {
+        tracker.observe(&GameEvent::GameStarted {
+            dealer: EPlayer::NPC,
+            seed: SEED,
+        });
+        tracker.observe(&GameEvent::TrumpRevealed(trump_card));
+    }
+
+    /// Deals the player the first cards of the deck that are not the trump card
+    fn deal(tracker: &mut CardTracker, count: usize) {
+        let trump_card = tracker.trump_card.clone();
+        for card in get_deck()
+            .into_iter()
+            .filter(|c| Some(c) != trump_card.as_ref())
+            .take(count)
+        {
+            tracker.observe(&GameEvent::CardDealt {
+                player: tracker.player,
+                card,
+            });
+        }
+    }
+
+    #[test]
+    fn voids_are_inferred_once_the_talon_is_closed() {
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, SEED);
+        start(&mut tracker, card(ESuit::Hearts, EValue::Ace));
+        deal(&mut tracker, 5);
+
+        // the open talon allows any card
+        tracker.observe(&play(EPlayer::PC, ESuit::Bells, EValue::King));
+        tracker.observe(&play(EPlayer::NPC, ESuit::Acorns, EValue::Unter));
+        assert!(tracker.get_voids().is_empty());
+
+        tracker.observe(&GameEvent::TalonClosed {
+            player: EPlayer::PC,
+        });
+        tracker.observe(&play(EPlayer::PC, ESuit::Bells, EValue::Ober));
+        tracker.observe(&play(EPlayer::NPC, ESuit::Acorns, EValue::King));
+        assert_eq!(tracker.get_voids(), [ESuit::Bells, ESuit::Hearts]);
+    }
+
+    #[test]
+    fn voids_are_inferred_once_the_talon_is_exhausted() {
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, SEED);
+        start(&mut tracker, card(ESuit::Hearts, EValue::Ace));
+        deal(&mut tracker, CARDS_PER_GAME - 1);
+        tracker.observe(&play(EPlayer::PC, ESuit::Leaves, EValue::King));
+        tracker.observe(&play(EPlayer::NPC, ESuit::Acorns, EValue::Unter));
+        assert!(tracker.get_voids().is_empty());
+
+        deal(&mut tracker, 1);
+        // trumping shows a void in the lead suit only
+        tracker.observe(&play(EPlayer::PC, ESuit::Leaves, EValue::Ober));
+        tracker.observe(&play(EPlayer::NPC, ESuit::Hearts, EValue::Unter));
+        assert_eq!(tracker.get_voids(), [ESuit::Leaves]);
+    }
+
+    #[test]
+    fn opponent_draws_the_trump_card_at_exhaustion() {
+        let trump_card = card(ESuit::Hearts, EValue::Ace);
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, SEED);
+        start(&mut tracker, trump_card.clone());
+        deal(&mut tracker, CARDS_PER_GAME);
+        assert_eq!(tracker.opponent_cards, std::slice::from_ref(&trump_card));
+
+        // the player drew it
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, SEED);
+        start(&mut tracker, trump_card.clone());
+        deal(&mut tracker, CARDS_PER_GAME - 1);
+        tracker.observe(&GameEvent::CardDealt {
+            player: EPlayer::PC,
+            card: trump_card,
+        });
+        assert!(tracker.opponent_cards.is_empty());
+        assert!(tracker.strict);
+    }
+
+    #[test]
+    fn impossible_cards_are_known_and_void() {
+        let mut tracker = CardTracker::new(EPlayer::PC, 0.0, SEED);
+        start(&mut tracker, card(ESuit::Hearts, EValue::Ace));
+        tracker.observe(&GameEvent::TalonClosed {
+            player: EPlayer::PC,
+        });
+        tracker.observe(&play(EPlayer::PC, ESuit::Acorns, EValue::Unter));
+        tracker.observe(&play(EPlayer::NPC, ESuit::Bells, EValue::King));
+        assert_eq!(tracker.get_voids(), [ESuit::Acorns, ESuit::Hearts]);
+
+        let game = get_endgame(
+            vec![card(ESuit::Leaves, EValue::Ace)],
+            vec![card(ESuit::Bells, EValue::X)],
+        );
+        let expected: Vec<Card> = get_deck()
+            .into_iter()
+            .filter(|c| {
+                *c == card(ESuit::Leaves, EValue::Ace)
+                    || *c == card(ESuit::Bells, EValue::King)
+                    || c.suit == ESuit::Acorns
+                    || c.suit == ESuit::Hearts
+            })
+            .collect();
+        assert_eq!(tracker.get_impossible(&game.view(EPlayer::PC)), expected);
+    }
+
+    #[test]
+    fn forget_rate_drops_cards() {
+        let events = [
+            GameEvent::MarriageDeclared {
+                player: EPlayer::NPC,
+                suit: ESuit::Bells,
+                points: 20,
+            },
+            play(EPlayer::NPC, ESuit::Leaves, EValue::Ace),
+            play(EPlayer::PC, ESuit::Leaves, EValue::X),
+        ];
+        let observe = |forget_rate: f64| {
+            let mut tracker = CardTracker::new(EPlayer::PC, forget_rate, SEED);
+            start(&mut tracker, card(ESuit::Hearts, EValue::Ace));
+            for event in &events {
+                tracker.observe(event);
+            }
+            tracker
+        };
+
+        let tracker = observe(0.0);
+        assert_eq!(tracker.opponent_cards.len(), 2);
+        assert_eq!(tracker.played.len(), 2);
+
+        let tracker = observe(1.0);
+        assert!(tracker.opponent_cards.is_empty());
+        assert!(tracker.played.is_empty());
+    }
+
+    #[test]
+    fn forgetting_depends_on_the_seeds() {
+        let observe = |seed: u64, game_seed: u64| {
+            let mut tracker = CardTracker::new(EPlayer::PC, 0.5, seed);
+            tracker.observe(&GameEvent::GameStarted {
+                dealer: EPlayer::NPC,
+                seed: game_seed,
+            });
+            for card in get_deck() {
+                tracker.observe(&GameEvent::CardPlayed {
+                    player: EPlayer::PC,
+                    card,
+                });
+            }
+            tracker.played
+        };
+
+        assert_eq!(observe(SEED, 1), observe(SEED, 1));
+        assert_ne!(observe(SEED, 1), observe(SEED, 2));
+        assert_ne!(observe(SEED, 1), observe(SEED + 1, 1));
+    }
+}
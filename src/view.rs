@@ -0,0 +1,312 @@
+use std::collections::VecDeque;
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    get_deck, get_marriage_value, get_opponent, must_follow_suit, Action, Card, EGameState,
+    EPlayer, ESuit, Game, GameResult, Variant,
+};
+
+/// What a player can see of a [`Game`]: the own hand and all cards that were shown on the table
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PlayerView {
+    pub player: EPlayer,
+    pub hand: Vec<Card>,
+    pub trump_suit: Option<ESuit>,
+    /// the face-up trump card under the talon
+    pub trump_card: Option<Card>,
+    pub trick: (Option<Card>, Option<Card>),
+    pub forehand: Option<EPlayer>,
+    /// the cards in the stack of the player
+    pub stack: Vec<Card>,
+    /// the cards in the stack of the opponent
+    pub opponent_stack: Vec<Card>,
+    /// the number of face-down cards in the talon
+    pub talon_len: usize,
+    /// the number of cards in the hand of the opponent
+    pub opponent_hand_len: usize,
+    /// the cards in the hand of the opponent that were shown, e.g. the exchanged trump card or a marriage
+    pub known_cards: Vec<Card>,
+    /// all declared marriages
+    pub marriages: Vec<(EPlayer, ESuit)>,
+    pub closed_by: Option<EPlayer>,
+    /// the points of the opponent of the closing player at the time of closing
+    pub closed_points: usize,
+    pub marriage_lead: Option<ESuit>,
+    pub points: usize,
+    pub opponent_points: usize,
+    pub phase: EGameState,
+    pub winner: Option<GameResult>,
+    pub variant: Variant,
+    /// the actions the player may take now, empty if it is not the turn of the player
+    pub legal_actions: Vec<Action>,
+}
+
+impl PlayerView {
+    pub fn new(game: &Game, player: EPlayer) -> Self {
+        let (hand, stack, opponent_hand, opponent_stack) = match player {
+            EPlayer::PC => (
+                &game.player_hand,
+                &game.player_stack,
+                &game.npc_hand,
+                &game.npc_stack,
+            ),
+            EPlayer::NPC => (
+                &game.npc_hand,
+                &game.npc_stack,
+                &game.player_hand,
+                &game.player_stack,
+            ),
+        };
+        // shown cards stay known until they are played
+        let opponent = get_opponent(player);
+        let mut known_cards: Vec<Card> = vec![];
+        for (_, card) in game.shown.iter().filter(|(p, _)| *p == opponent) {
+            if opponent_hand.contains(card) && !known_cards.contains(card) {
+                known_cards.push(card.clone());
+            }
+        }
+        let legal_actions = if game.is_turn_of(player) {
+            game.legal_actions()
+        } else {
+            vec![]
+        };
+
+        Self {
+            player,
+            hand: hand.clone(),
+            trump_suit: game.trump_suit,
+            trump_card: game.trump_card.clone(),
+            trick: game.trick.clone(),
+            forehand: game.forehand,
+            stack: stack.clone(),
+            opponent_stack: opponent_stack.clone(),
+            talon_len: game.talon.len(),
+            opponent_hand_len: opponent_hand.len(),
+            known_cards,
+            marriages: game.marriages.clone(),
+            closed_by: game.closed_by,
+            closed_points: game.closed_points,
+            marriage_lead: game.marriage_lead,
+            points: game.get_points(player),
+            opponent_points: game.get_points(opponent),
+            phase: game.phase(),
+            winner: game.winner,
+            variant: game.variant.clone(),
+            legal_actions,
+        }
+    }
+
+    /// Checks if the player may take the action now
+    pub fn can(&self, action: &Action) -> bool {
+        self.legal_actions.contains(action)
+    }
+
+    /// Gets the cards the player may play now
+    pub fn legal_moves(&self) -> Vec<Card> {
+        self.legal_actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::Play(card) => Some(card.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the marriages the player may declare now
+    pub fn get_marriages(&self) -> Vec<ESuit> {
+        self.legal_actions
+            .iter()
+            .filter_map(|a| match a {
+                Action::DeclareMarriage(suit) => Some(*suit),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The value of a marriage in the suit, see [`get_marriage_value`]
+    pub fn get_marriage_value(&self, suit: ESuit) -> usize {
+        get_marriage_value(suit, self.trump_suit)
+    }
+
+    /// Checks if the strict rules apply, see [`must_follow_suit`]
+    pub fn must_follow_suit(&self) -> bool {
+        must_follow_suit(self.trump_card.as_ref(), self.closed_by)
+    }
+
+    pub fn is_trump(&self, card: &Card) -> bool {
+        Some(card.suit) == self.trump_suit
+    }
+
+    /// Gets all cards that were played so far: both stacks and the current trick
+    pub fn get_played(&self) -> Vec<Card> {
+        self.stack
+            .iter()
+            .chain(self.opponent_stack.iter())
+            .chain(self.trick.0.iter())
+            .chain(self.trick.1.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Gets all cards the player has not seen yet, they are in the hand of the opponent or in the talon
+    pub fn get_unseen(&self) -> Vec<Card> {
+        let played = self.get_played();
+        get_deck()
+            .into_iter()
+            .filter(|c| {
+                !self.hand.contains(c)
+                    && !played.contains(c)
+                    && !self.known_cards.contains(c)
+                    && self.trump_card.as_ref() != Some(c)
+            })
+            .collect()
+    }
+
+    /// Samples a [`Game`] that is consistent with everything the player knows:
+    /// the unseen cards are dealt randomly to the hand of the opponent and the talon.
+    /// Once the talon is exhausted the sampled game is the real one.
+    pub fn determinize<R: Rng + ?Sized>(&self, rng: &mut R) -> Game {
+        self.deal(self.known_cards.clone(), self.get_unseen(), &[], rng)
+    }
+
+    /// Deals the unseen cards randomly to the hand of the opponent and the talon.
+    /// Cards of the void suits only go to the opponent if there are not enough other cards.
+    pub(crate) fn deal<R: Rng + ?Sized>(
+        &self,
+        mut opponent_hand: Vec<Card>,
+        mut unseen: Vec<Card>,
+        voids: &[ESuit],
+        rng: &mut R,
+    ) -> Game {
+        unseen.shuffle(rng);
+        unseen.sort_by_key(|c| voids.contains(&c.suit));
+
+        let missing = self.opponent_hand_len.saturating_sub(opponent_hand.len());
+        opponent_hand.extend(unseen.drain(..missing.min(unseen.len())));
+        unseen.shuffle(rng);
+        unseen.truncate(self.talon_len);
+
+        let (player_hand, player_stack, npc_hand, npc_stack) = match self.player {
+            EPlayer::PC => (
+                self.hand.clone(),
+                self.stack.clone(),
+                opponent_hand,
+                self.opponent_stack.clone(),
+            ),
+            EPlayer::NPC => (
+                opponent_hand,
+                self.opponent_stack.clone(),
+                self.hand.clone(),
+                self.stack.clone(),
+            ),
+        };
+
+        Game {
+            trump_suit: self.trump_suit,
+            trump_card: self.trump_card.clone(),
+            talon: unseen,
+            trick: self.trick.clone(),
+            player_stack,
+            player_hand,
+            npc_stack,
+            npc_hand,
+            marriages: self.marriages.clone(),
+            shown: vec![],
+            variant: self.variant.clone(),
+            forehand: self.forehand,
+            winner: self.winner,
+            closed_by: self.closed_by,
+            closed_points: self.closed_points,
+            marriage_lead: self.marriage_lead,
+            seed: rng.gen(),
+            dealer: None,
+            actions: vec![],
+            rng_draws: 0,
+            state: self.phase.clone(),
+            events: VecDeque::new(),
+            history: vec![],
+        }
+    }
+
+    /// Checks if the player can deduce the hand of the opponent, e.g. after the talon is exhausted
+    pub fn knows_all_cards(&self) -> bool {
+        self.known_cards.len() + self.get_unseen().len() == self.opponent_hand_len
+    }
+
+    /// Gets all cards the opponent may hold: the known cards and the unseen ones
+    pub fn get_opponent_candidates(&self) -> Vec<Card> {
+        let mut cards = self.known_cards.clone();
+        cards.extend(self.get_unseen());
+        cards
+    }
+}
+
+impl Game {
+    /// Gets what the player can see of this [`Game`]
+    pub fn view(&self, player: EPlayer) -> PlayerView {
+        PlayerView::new(self, player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::EValue;
+
+    const SEED: u64 = 42;
+
+    #[test]
+    fn view_hides_the_opponent_hand_and_the_talon() {
+        let mut game = Game::with_seed(SEED);
+        game.play_with_dealer(EPlayer::PC).unwrap();
+
+        let view = game.view(EPlayer::NPC);
+        assert_eq!(view.hand, game.npc_hand);
+        assert_eq!(view.talon_len, game.talon.len());
+        assert_eq!(view.opponent_hand_len, game.player_hand.len());
+        assert!(view.known_cards.is_empty());
+
+        // the hand of the PC and the talon are unseen, in no particular order
+        let mut hidden = game.player_hand.clone();
+        hidden.extend(game.talon.iter().cloned());
+        let unseen = view.get_unseen();
+        assert_eq!(unseen.len(), hidden.len());
+        assert!(hidden.iter().all(|c| unseen.contains(c)));
+
+        // shuffling what the NPC can't see leaves its view unchanged
+        let mut shuffled = game.clone();
+        shuffled.talon.reverse();
+        shuffled.player_hand.reverse();
+        assert_eq!(
+            format!("{:?}", shuffled.view(EPlayer::NPC)),
+            format!("{:?}", view)
+        );
+    }
+
+    #[test]
+    fn view_keeps_the_exchanged_trump() {
+        // the first deal where the NPC leads and may exchange the trump Unter
+        let mut game = (0..)
+            .map(|seed| {
+                let mut game = Game::with_seed(seed);
+                game.play_with_dealer(EPlayer::PC).unwrap();
+                game
+            })
+            .find(|game| game.can_exchange_trump(EPlayer::NPC))
+            .unwrap();
+        let trump_card = game.trump_card.clone().unwrap();
+        game.apply(Action::ExchangeTrump).unwrap();
+
+        let view = game.view(EPlayer::PC);
+        assert_eq!(view.known_cards, vec![trump_card.clone()]);
+        assert_eq!(
+            view.trump_card,
+            Some(Card::new(trump_card.suit, EValue::Unter))
+        );
+        assert!(!view.get_unseen().contains(&trump_card));
+        assert!(view.get_opponent_candidates().contains(&trump_card));
+        assert!(game.view(EPlayer::NPC).hand.contains(&trump_card));
+    }
+}